@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use ::*;
+
+/// Default number of queries running at the same time.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Query builder running the same query for many locations concurrently.
+///
+/// Units and language apply to every query. Queries still go through the
+/// rate limit of the hub, if any.
+pub struct BatchQuery<'a> {
+    hub: &'a WeatherHub,
+    builder: uri::UriBuilder<'a>,
+    concurrency: usize,
+}
+
+impl<'a> uri::HasBuilder<'a> for BatchQuery<'a> {
+    fn builder(&mut self) -> &mut uri::UriBuilder<'a> {
+        &mut self.builder
+    }
+}
+
+impl<'a> self::FormatResponse<'a> for BatchQuery<'a> {}
+
+impl<'a> BatchQuery<'a> {
+    pub fn new(hub: &'a WeatherHub, builder: uri::UriBuilder<'a>) -> BatchQuery<'a> {
+        BatchQuery {
+            hub,
+            builder,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Change the maximum number of queries running at the same time.
+    /// Default is 4.
+    pub fn concurrency(mut self, concurrency: usize) -> BatchQuery<'a> {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Queries the current weather at each location. Results are returned in
+    /// the order of the locations, and a failed query doesn't affect the
+    /// others.
+    pub fn current(self,
+                   locations: &[Location])
+                   -> Vec<Result<(hyper::client::Response, WeatherInfo)>> {
+        let next = AtomicUsize::new(0);
+        let workers = self.concurrency.min(locations.len());
+
+        let mut results: Vec<(usize, Result<_>)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = Vec::new();
+
+                        loop {
+                            let i = next.fetch_add(1, Ordering::Relaxed);
+                            match locations.get(i) {
+                                Some(location) => {
+                                    let query = CurrentWeatherQuery::new(self.hub,
+                                                                         self.builder.clone());
+                                    done.push((i, query.at(location)));
+                                }
+                                None => return done,
+                            }
+                        }
+                    })
+                })
+                .collect();
+
+            handles.into_iter()
+                .flat_map(|handle| handle.join().expect("batch worker panicked"))
+                .collect()
+        });
+
+        results.sort_by_key(|&(i, _)| i);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+}
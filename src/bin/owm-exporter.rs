@@ -0,0 +1,160 @@
+//! Prometheus exporter for the weather of the locations saved in the
+//! configuration file.
+//!
+//! Build with `cargo build --features exporter`, then run `owm-exporter --help`.
+
+extern crate clap;
+extern crate hyper;
+extern crate owm;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::path::PathBuf;
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use clap::{value_parser, Arg, ArgMatches, Command};
+use hyper::header::ContentType;
+use hyper::server::{Request, Response, Server};
+use hyper::status::StatusCode;
+use hyper::uri::RequestUri;
+use owm::config::Config;
+use owm::data::WeatherInfo;
+use owm::{prometheus, Error, Location, Units, WeatherHub};
+
+const EXIT_OTHER: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_AUTH: i32 = 4;
+
+/// Last observation of each location, and whether its last query succeeded.
+type Observations = BTreeMap<String, (Option<WeatherInfo>, bool)>;
+
+fn main() {
+    let matches = cli().get_matches();
+    let config = match config(&matches) {
+        Ok(config) => config,
+        Err(Error::InvalidConfig(msg)) => fail(EXIT_USAGE, &format!("invalid configuration: {}", msg)),
+        Err(Error::IoError(err)) => fail(EXIT_OTHER, &format!("cannot read the configuration: {}", err)),
+        Err(err) => fail(EXIT_OTHER, &format!("{:?}", err)),
+    };
+    if config.locations.is_empty() {
+        fail(EXIT_USAGE, "no locations saved in the configuration, see --help");
+    }
+
+    // Gauges are named after metric units
+    let hub = match config.hub(hyper::Client::new()) {
+        Ok(hub) => Arc::new(hub.units(Units::Metric)),
+        Err(_) => fail(EXIT_AUTH, "no API key given, see --help"),
+    };
+    let observations = Arc::new(Mutex::new(Observations::new()));
+    let interval = Duration::from_secs(*matches.get_one::<u64>("interval").expect("has a default"));
+
+    {
+        let (hub, observations) = (hub.clone(), observations.clone());
+        let locations = config.locations.clone();
+        thread::spawn(move || loop {
+            poll(&hub, &locations, &observations);
+            thread::sleep(interval);
+        });
+    }
+
+    let listen = matches.get_one::<String>("listen").expect("has a default");
+    let server = Server::http(listen.as_str())
+        .unwrap_or_else(|err| fail(EXIT_OTHER, &format!("cannot listen on {}: {}", listen, err)));
+    let listening = server.handle(move |req: Request, mut res: Response| {
+            let path = match req.uri {
+                RequestUri::AbsolutePath(ref path) => path.split('?').next().unwrap_or(""),
+                _ => "",
+            };
+
+            let body = match path {
+                "/metrics" => {
+                    res.headers_mut().set(ContentType("text/plain; version=0.0.4".parse().unwrap()));
+                    metrics(&hub, &observations)
+                }
+                "/" => "owm-exporter, see /metrics\n".to_string(),
+                _ => {
+                    *res.status_mut() = StatusCode::NotFound;
+                    "not found\n".to_string()
+                }
+            };
+            let _ = res.send(body.as_bytes());
+        })
+        .unwrap_or_else(|err| fail(EXIT_OTHER, &format!("cannot serve: {}", err)));
+
+    eprintln!("owm-exporter: serving metrics on http://{}/metrics", listening.socket);
+    // Dropping the listener waits for the server, which runs forever
+}
+
+fn cli() -> Command {
+    Command::new("owm-exporter")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Serves the current weather of the saved locations as Prometheus metrics")
+        .after_help("The API key, base URL, cache, rate limit and locations are read from the \
+                     configuration file and the OWM_* environment variables, as for the owm \
+                     command. Observations are always fetched in metric units.")
+        .arg(Arg::new("config")
+            .long("config")
+            .value_parser(value_parser!(PathBuf))
+            .help("TOML configuration file [default: $OWM_CONFIG, or \
+                   ~/.config/owm/config.toml]"))
+        .arg(Arg::new("listen")
+            .long("listen")
+            .default_value("127.0.0.1:9610")
+            .help("Address to serve the metrics on"))
+        .arg(Arg::new("interval")
+            .long("interval")
+            .value_parser(value_parser!(u64).range(1..))
+            .default_value("600")
+            .help("Seconds between two queries of the locations"))
+}
+
+fn config(matches: &ArgMatches) -> owm::Result<Config> {
+    match matches.get_one::<PathBuf>("config") {
+        Some(path) => {
+            Ok(Config::from_path(path)?.with_env(|name| env::var(name).ok())?.with_key_file())
+        }
+        None => Config::load(),
+    }
+}
+
+/// Queries all the locations and records their observations.
+fn poll(hub: &WeatherHub, locations: &BTreeMap<String, Location>, observations: &Mutex<Observations>) {
+    let (names, locations): (Vec<&String>, Vec<Location>) =
+        locations.iter().map(|(name, location)| (name, location.clone())).unzip();
+    let results = hub.batch().current(&locations);
+
+    let mut observations = observations.lock().unwrap_or_else(|e| e.into_inner());
+    for (name, result) in names.into_iter().zip(results) {
+        let entry = observations.entry(name.clone()).or_insert((None, false));
+        match result {
+            Ok((_, info)) => *entry = (Some(info), true),
+            Err(err) => {
+                entry.1 = false;
+                eprintln!("owm-exporter: cannot query {}: {}", name, err.kind().name());
+            }
+        }
+    }
+}
+
+fn metrics(hub: &WeatherHub, observations: &Mutex<Observations>) -> String {
+    let observations = observations.lock().unwrap_or_else(|e| e.into_inner());
+    let mut out = Vec::new();
+
+    let weather = observations.iter()
+        .filter_map(|(name, (info, _))| info.as_ref().map(|info| (name.as_str(), info)));
+    let up = observations.iter().map(|(name, &(_, up))| (name.as_str(), up));
+
+    prometheus::write_weather(&mut out, weather)
+        .and_then(|_| prometheus::write_location_up(&mut out, up))
+        .and_then(|_| prometheus::write_stats(&mut out, &hub.stats()))
+        .expect("writing to memory doesn't fail");
+
+    String::from_utf8(out).expect("metrics are valid UTF-8")
+}
+
+fn fail(code: i32, message: &str) -> ! {
+    eprintln!("owm-exporter: {}", message);
+    process::exit(code)
+}
@@ -0,0 +1,392 @@
+//! Command-line client for the current weather API.
+//!
+//! Build with `cargo build --features cli`, then run `owm --help`.
+
+extern crate clap;
+extern crate hyper;
+extern crate owm;
+
+use std::env;
+use std::path::PathBuf;
+use std::process;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use owm::format::{Format, Render};
+use owm::{BoundingBox, Error, ErrorKind, Location, Units};
+use owm::city::CityList;
+use owm::config::Config;
+use owm::influx::{self, LineProtocol, Precision};
+use owm::template::{IconSet, Template};
+
+const EXIT_OTHER: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_NETWORK: i32 = 3;
+const EXIT_AUTH: i32 = 4;
+const EXIT_NOT_FOUND: i32 = 5;
+const EXIT_DECODE: i32 = 6;
+
+const DEFAULT_TEMPLATE: &str = "{temp:.0}{temp_units} {condition_icon}";
+
+const EXIT_CODES: &str = "\
+Exit codes:
+  0  success
+  1  other error, e.g. unreadable file
+  2  invalid arguments, query or configuration
+  3  network or server error
+  4  missing or rejected API key
+  5  location not found
+  6  unexpected response from the server";
+
+fn main() {
+    let matches = cli().get_matches();
+    let config = match config(&matches) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("owm: {}", describe(&err));
+            process::exit(exit_code(&err));
+        }
+    };
+    if config.api_key.is_none() {
+        eprintln!("owm: no API key given, see --help");
+        process::exit(EXIT_AUTH);
+    }
+
+    match run(&matches, &config) {
+        Ok(output) => println!("{}", output),
+        Err(err) => {
+            eprintln!("owm: {}", describe(&err));
+            process::exit(exit_code(&err));
+        }
+    }
+}
+
+fn cli() -> Command {
+    let country = Arg::new("country")
+        .long("country")
+        .short('c')
+        .help("Country code, e.g. GB");
+    let coord = |name: &'static str| {
+        Arg::new(name)
+            .required(true)
+            .allow_negative_numbers(true)
+            .value_parser(value_parser!(f64))
+    };
+    let cluster = Arg::new("cluster")
+        .long("cluster")
+        .action(ArgAction::SetTrue)
+        .help("Let the server cluster nearby cities");
+
+    Command::new("owm")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Fetches the current weather from OpenWeatherMap")
+        .subcommand_required(true)
+        .after_help(EXIT_CODES)
+        .arg(Arg::new("api-key")
+            .long("api-key")
+            .global(true)
+            .help("API key [default: $OWM_API_KEY, or the configuration file, or the content \
+                   of ~/.config/owm/api_key]"))
+        .arg(Arg::new("config")
+            .long("config")
+            .global(true)
+            .value_parser(value_parser!(PathBuf))
+            .help("TOML configuration file [default: $OWM_CONFIG, or \
+                   ~/.config/owm/config.toml]"))
+        .arg(Arg::new("units")
+            .long("units")
+            .short('u')
+            .global(true)
+            .value_parser(["standard", "metric", "imperial"])
+            .help("Units of the results [default: $OWM_UNITS, or the configuration file, or \
+                   standard]"))
+        .arg(Arg::new("lang")
+            .long("lang")
+            .short('l')
+            .global(true)
+            .help("Language of the weather descriptions, e.g. it"))
+        .arg(Arg::new("output")
+            .long("output")
+            .short('o')
+            .global(true)
+            .value_parser(["table", "json", "csv", "csv-rows", "ndjson", "text", "i3bar", "waybar", "influx"])
+            .default_value("table")
+            .help("Output format. csv-rows leaves out the CSV header, to append to a file, \
+                   text, i3bar and waybar print one templated line per city, \
+                   for status bars, influx prints InfluxDB line protocol"))
+        .arg(Arg::new("template")
+            .long("template")
+            .short('t')
+            .global(true)
+            .default_value(DEFAULT_TEMPLATE)
+            .help("Template of the text, i3bar and waybar outputs, e.g. \
+                   \"{city} {temp:.1}{temp_units} {wind_compass}\""))
+        .arg(Arg::new("icons")
+            .long("icons")
+            .global(true)
+            .value_parser(["emoji", "nerd-font", "weather-icons"])
+            .default_value("emoji")
+            .help("Icons of the {condition_icon} placeholder"))
+        .arg(Arg::new("fields")
+            .long("fields")
+            .global(true)
+            .value_delimiter(',')
+            .value_parser(influx::FIELDS.iter().map(|&(key, _)| key).collect::<Vec<_>>())
+            .hide_possible_values(true)
+            .help("Comma-separated fields of the influx output, e.g. temp,humidity [default: \
+                   all]"))
+        .arg(Arg::new("precision")
+            .long("precision")
+            .global(true)
+            .value_parser(["s", "ms", "us", "ns"])
+            .default_value("ns")
+            .help("Unit of the timestamps of the influx output"))
+        .arg(Arg::new("base-url").long("base-url").global(true).hide(true))
+        .subcommand(Command::new("name")
+            .about("Weather by city name")
+            .arg(Arg::new("city").required(true))
+            .arg(country.clone())
+            .arg(Arg::new("state")
+                .long("state")
+                .short('s')
+                .help("State code, for US cities [country: US]"))
+            .arg(Arg::new("city-list")
+                .long("city-list")
+                .value_parser(value_parser!(PathBuf))
+                .help("Resolve the name offline against OWM's city.list.json, failing if it \
+                       is ambiguous")))
+        .subcommand(Command::new("saved")
+            .about("Weather at a location saved in the configuration file")
+            .arg(Arg::new("location").required(true)))
+        .subcommand(Command::new("id")
+            .about("Weather by city ID")
+            .arg(Arg::new("id").required(true).value_parser(value_parser!(i32))))
+        .subcommand(Command::new("zip")
+            .about("Weather by ZIP or postal code")
+            .arg(Arg::new("code").required(true))
+            .arg(country))
+        .subcommand(Command::new("coords")
+            .about("Weather at geographic coordinates")
+            .arg(coord("lat"))
+            .arg(coord("lon")))
+        .subcommand(Command::new("bbox")
+            .about("Weather of the cities within a bounding box")
+            .arg(coord("top"))
+            .arg(coord("bottom"))
+            .arg(coord("left"))
+            .arg(coord("right"))
+            .arg(Arg::new("zoom")
+                .long("zoom")
+                .value_parser(value_parser!(i32))
+                .default_value("10"))
+            .arg(cluster.clone())
+            .arg(Arg::new("tiled")
+                .long("tiled")
+                .action(ArgAction::SetTrue)
+                .help("Split boxes larger than the server allows into several queries")))
+        .subcommand(Command::new("circle")
+            .about("Weather of the cities around a point")
+            .arg(coord("lat"))
+            .arg(coord("lon"))
+            .arg(Arg::new("count")
+                .long("count")
+                .short('n')
+                .value_parser(value_parser!(i32))
+                .default_value("10")
+                .help("Number of cities"))
+            .arg(cluster))
+}
+
+fn run(matches: &ArgMatches, config: &Config) -> owm::Result<String> {
+    let hub = config.hub(hyper::Client::new())?;
+    let format = output(matches, config.units.unwrap_or(Units::Standard))?;
+    let query = hub.current();
+
+    match matches.subcommand() {
+        Some(("name", args)) => {
+            let city = arg::<String>(args, "city");
+            let state = args.get_one::<String>("state").cloned();
+            let country = args.get_one::<String>("country")
+                .cloned()
+                .or_else(|| state.as_ref().map(|_| "US".to_string()));
+
+            match args.get_one::<PathBuf>("city-list") {
+                Some(path) => {
+                    let cities = CityList::from_path(path)?;
+                    let parts: Vec<String> =
+                        Some(city).into_iter().chain(state).chain(country).collect();
+                    render(&format, query.by_name_strict(&parts.join(", "), &cities)?.1)
+                }
+                None => {
+                    render(&format, query.at(&Location::Name {
+                            city,
+                            state,
+                            country,
+                        })?
+                        .1)
+                }
+            }
+        }
+        Some(("saved", args)) => {
+            render(&format, query.at(config.location(&arg::<String>(args, "location"))?)?.1)
+        }
+        Some(("id", args)) => render(&format, query.at(&Location::id(arg(args, "id")))?.1),
+        Some(("zip", args)) => {
+            let country = args.get_one::<String>("country").map(String::as_str);
+            render(&format, query.by_postal_code(&arg::<String>(args, "code"), country)?.1)
+        }
+        Some(("coords", args)) => {
+            render(&format, query.at(&Location::coords(arg(args, "lat"), arg(args, "lon"))?)?.1)
+        }
+        Some(("bbox", args)) => {
+            let bbox = BoundingBox::new(arg(args, "top"),
+                                        arg(args, "bottom"),
+                                        arg(args, "left"),
+                                        arg(args, "right"))?;
+            let (zoom, cluster) = (arg(args, "zoom"), args.get_flag("cluster"));
+
+            if args.get_flag("tiled") {
+                render(&format, query.by_bounds_tiled(&bbox, zoom, cluster)?.1)
+            } else {
+                render(&format, query.by_bounds(&bbox, zoom, cluster)?.1)
+            }
+        }
+        Some(("circle", args)) => {
+            let (lat, lon) = (arg::<f64>(args, "lat") as f32, arg::<f64>(args, "lon") as f32);
+            render(&format, query.by_circle(lat, lon, arg(args, "count"), args.get_flag("cluster"))?.1)
+        }
+        _ => unreachable!("subcommand is required"),
+    }
+}
+
+/// Value of an argument that is either required or has a default value.
+fn arg<T: Clone + Send + Sync + 'static>(args: &ArgMatches, name: &str) -> T {
+    args.get_one::<T>(name).cloned().expect("argument is required or has a default")
+}
+
+/// Rendering of the results, either a format of the library or a template.
+enum Output {
+    Format(Format),
+    Text(Template),
+    I3bar(Template),
+    Waybar(Template),
+    Influx(LineProtocol),
+}
+
+fn output(matches: &ArgMatches, units: Units) -> owm::Result<Output> {
+    let name = arg::<String>(matches, "output");
+    if let Ok(format) = name.parse() {
+        return Ok(Output::Format(format));
+    }
+    if name == "influx" {
+        let precision: Precision = arg::<String>(matches, "precision").parse().expect("checked by clap");
+        let mut influx = LineProtocol::new().precision(precision);
+        if let Some(fields) = matches.get_many::<String>("fields") {
+            influx = influx.fields(fields.map(String::as_str)).expect("checked by clap");
+        }
+        return Ok(Output::Influx(influx));
+    }
+
+    let icons = match arg::<String>(matches, "icons").as_str() {
+        "nerd-font" => IconSet::NerdFont,
+        "weather-icons" => IconSet::WeatherIcons,
+        _ => IconSet::Emoji,
+    };
+    let template = Template::parse(&arg::<String>(matches, "template"))?
+        .units(units)
+        .icons(icons);
+
+    Ok(match name.as_str() {
+        "i3bar" => Output::I3bar(template),
+        "waybar" => Output::Waybar(template),
+        _ => Output::Text(template),
+    })
+}
+
+fn render<R: Render>(output: &Output, value: R) -> owm::Result<String> {
+    let lines: Vec<String> = match *output {
+        Output::Format(format) => return Ok(value.render(format).trim_end().to_string()),
+        Output::Influx(ref influx) => return Ok(influx.render(&value).trim_end().to_string()),
+        Output::Text(ref template) => value.rows().iter().map(|row| template.render(row)).collect(),
+        Output::I3bar(ref template) => {
+            value.rows().iter().map(|row| template.i3bar(row).to_string()).collect()
+        }
+        Output::Waybar(ref template) => {
+            value.rows().iter().map(|row| template.waybar(row).to_string()).collect()
+        }
+    };
+
+    Ok(lines.join("\n"))
+}
+
+/// Configuration from the file and the environment, overridden by the
+/// command line.
+fn config(matches: &ArgMatches) -> owm::Result<Config> {
+    let mut config = match matches.get_one::<PathBuf>("config") {
+        Some(path) => {
+            Config::from_path(path)?.with_env(|name| env::var(name).ok())?.with_key_file()
+        }
+        None => Config::load()?,
+    };
+
+    if let Some(key) = matches.get_one::<String>("api-key") {
+        config.api_key = Some(key.clone());
+    }
+    if let Some(url) = matches.get_one::<String>("base-url") {
+        config.base_url = Some(url.clone());
+    }
+    if let Some(units) = matches.get_one::<String>("units") {
+        config.units = Some(match units.as_str() {
+            "metric" => Units::Metric,
+            "imperial" => Units::Imperial,
+            _ => Units::Standard,
+        });
+    }
+    if let Some(lang) = matches.get_one::<String>("lang") {
+        config.lang = Some(lang.clone());
+    }
+
+    Ok(config)
+}
+
+fn describe(err: &Error) -> String {
+    match *err {
+        Error::HttpError(ref err) => format!("network error: {}", err),
+        Error::BadRequest(ref resp) => {
+            format!("request rejected: {}",
+                    resp.message.as_ref().map_or("unknown error", String::as_str))
+        }
+        Error::JsonDecodeError(_, ref err) => format!("unexpected response: {}", err),
+        Error::Failure(ref res) => format!("server replied with status {}", res.status),
+        Error::IoError(ref err) => err.to_string(),
+        Error::LocationNotFound(ref location) => format!("location not found: {}", location),
+        Error::AmbiguousLocation(ref candidates) => {
+            let names: Vec<String> = candidates.iter()
+                .map(|c| {
+                    let city = &c.city;
+                    match city.state {
+                        Some(ref state) => format!("{}, {}, {}", city.name, state, city.country),
+                        None => format!("{}, {}", city.name, city.country),
+                    }
+                })
+                .collect();
+            format!("ambiguous location, did you mean: {}?", names.join(" / "))
+        }
+        Error::InvalidCoordinates(ref msg) => format!("invalid coordinates: {}", msg),
+        Error::InvalidTemplate(ref msg) => format!("invalid template: {}", msg),
+        Error::InvalidConfig(ref msg) => format!("invalid configuration: {}", msg),
+        Error::InvalidRule(ref msg) => format!("invalid rule: {}", msg),
+        Error::StorageError(ref msg) => format!("storage error: {}", msg),
+        Error::MqttError(ref msg) => format!("MQTT error: {}", msg),
+        Error::InvalidObservation(ref msg) => format!("invalid observation: {}", msg),
+    }
+}
+
+fn exit_code(err: &Error) -> i32 {
+    match err.kind() {
+        ErrorKind::Network | ErrorKind::Server | ErrorKind::RateLimited => EXIT_NETWORK,
+        ErrorKind::Auth => EXIT_AUTH,
+        ErrorKind::NotFound => EXIT_NOT_FOUND,
+        ErrorKind::Decode => EXIT_DECODE,
+        ErrorKind::InvalidRequest => EXIT_USAGE,
+        ErrorKind::Io => EXIT_OTHER,
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use hyper::client::Response;
+use hyper::http::{HttpMessage, RequestHead, ResponseHead};
+use hyper::Url;
+
+/// Keeps the bodies of successful responses for a while, keyed by query URL.
+pub struct ResponseCache {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+struct Entry {
+    stored: Instant,
+    head: ResponseHead,
+    body: String,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration, capacity: usize) -> ResponseCache {
+        ResponseCache {
+            ttl,
+            capacity: capacity.max(1),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Response and body stored for the query, if they haven't expired.
+    pub fn get(&self, query: &str) -> Option<(Response, String)> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = entries.get(&key(query)).filter(|entry| entry.stored.elapsed() < self.ttl)?;
+
+        let message = CachedMessage { head: Some(entry.head.clone()) };
+        let res = Response::with_message(Url::parse(query).ok()?, Box::new(message)).ok()?;
+
+        Some((res, entry.body.clone()))
+    }
+
+    /// Stores the response to the query, evicting the oldest entry if full.
+    pub fn put(&self, query: &str, res: &Response, body: &str) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let ttl = self.ttl;
+        entries.retain(|_, entry| entry.stored.elapsed() < ttl);
+
+        if entries.len() >= self.capacity {
+            let oldest = entries.iter().min_by_key(|&(_, entry)| entry.stored).map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(key(query),
+                       Entry {
+                           stored: Instant::now(),
+                           head: ResponseHead {
+                               headers: res.headers.clone(),
+                               raw_status: res.status_raw().clone(),
+                               version: res.version,
+                           },
+                           body: body.to_string(),
+                       });
+    }
+}
+
+/// Query URL with sorted parameters, as their order is not stable.
+fn key(query: &str) -> String {
+    match query.find('?') {
+        Some(i) => {
+            let mut params: Vec<&str> = query[i + 1..].split('&').collect();
+            params.sort_unstable();
+            format!("{}?{}", &query[..i], params.join("&"))
+        }
+        None => query.to_string(),
+    }
+}
+
+/// Message replaying a cached response head. The body is returned separately.
+#[derive(Debug)]
+struct CachedMessage {
+    head: Option<ResponseHead>,
+}
+
+impl HttpMessage for CachedMessage {
+    fn set_outgoing(&mut self, head: RequestHead) -> ::hyper::Result<RequestHead> {
+        Ok(head)
+    }
+
+    fn get_incoming(&mut self) -> ::hyper::Result<ResponseHead> {
+        self.head.take().ok_or(::hyper::Error::Io(io::ErrorKind::UnexpectedEof.into()))
+    }
+
+    fn set_read_timeout(&self, _: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_write_timeout(&self, _: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn close_connection(&mut self) -> ::hyper::Result<()> {
+        Ok(())
+    }
+
+    fn has_body(&self) -> bool {
+        false
+    }
+}
+
+impl Read for CachedMessage {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Ok(0)
+    }
+}
+
+impl Write for CachedMessage {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
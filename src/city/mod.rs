@@ -0,0 +1,215 @@
+//! Offline access to OpenWeatherMap's list of cities.
+//!
+//! OWM publishes the cities known to the API as a bulk file, `city.list.json.gz`,
+//! available at http://bulk.openweathermap.org/sample/. Once downloaded, it can
+//! be loaded into a [CityList](struct.CityList.html) to look up the city IDs
+//! accepted by [by_id](../current/struct.CurrentWeatherQuery.html#method.by_id).
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use flate2::read::GzDecoder;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+use {Error, Result};
+
+mod resolve;
+mod spatial;
+
+pub use self::resolve::Candidate;
+pub use self::spatial::SpatialIndex;
+
+/// A city known to the OWM API.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct City {
+    /// City ID
+    pub id: i32,
+    /// City name
+    pub name: String,
+    /// State code, for US cities
+    pub state: Option<String>,
+    /// Country code
+    pub country: String,
+    /// Latitude
+    pub lat: f64,
+    /// Longitude
+    pub lon: f64,
+    /// Population, when available in the bulk file
+    pub population: Option<u64>,
+}
+
+/// Entry of the bulk file, whose layout differs from the one of `City`.
+#[derive(Deserialize)]
+struct RawCity {
+    id: i32,
+    name: String,
+    #[serde(default)]
+    state: String,
+    country: String,
+    coord: RawCoord,
+    stat: Option<RawStat>,
+}
+
+#[derive(Deserialize)]
+struct RawCoord {
+    lon: f64,
+    lat: f64,
+}
+
+#[derive(Deserialize)]
+struct RawStat {
+    population: Option<u64>,
+}
+
+impl From<RawCity> for City {
+    fn from(raw: RawCity) -> City {
+        City {
+            id: raw.id,
+            name: raw.name,
+            state: if raw.state.is_empty() { None } else { Some(raw.state) },
+            country: raw.country,
+            lat: raw.coord.lat,
+            lon: raw.coord.lon,
+            population: raw.stat.and_then(|stat| stat.population),
+        }
+    }
+}
+
+/// In-memory index over the cities of the OWM bulk file.
+pub struct CityList {
+    cities: Vec<City>,
+    by_id: HashMap<i32, usize>,
+    by_name: HashMap<String, Vec<usize>>,
+    sorted: Vec<(String, usize)>,
+}
+
+impl CityList {
+    /// Loads the cities from a `city.list.json` file, optionally gzip-compressed.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<CityList> {
+        File::open(path).map_err(Error::IoError).and_then(CityList::from_reader)
+    }
+
+    /// Loads the cities from a reader over a `city.list.json` file, optionally gzip-compressed.
+    /// The file is decoded as it is read, so the body of decoding errors is left empty.
+    pub fn from_reader<R: Read>(reader: R) -> Result<CityList> {
+        let mut reader = BufReader::new(reader);
+        let gzipped = reader.fill_buf().map_err(Error::IoError)?.starts_with(&[0x1f, 0x8b]);
+
+        let decoded = if gzipped {
+            json::from_reader::<_, Vec<RawCity>>(BufReader::new(GzDecoder::new(reader)))
+        } else {
+            json::from_reader::<_, Vec<RawCity>>(reader)
+        };
+
+        match decoded {
+            Ok(raw) => Ok(CityList::new(raw.into_iter().map(City::from).collect())),
+            Err(err) if err.is_io() => Err(Error::IoError(err.into())),
+            Err(err) => Err(Error::JsonDecodeError(String::new(), err)),
+        }
+    }
+
+    /// Builds the index over the given cities.
+    pub fn new(cities: Vec<City>) -> CityList {
+        let mut by_id = HashMap::with_capacity(cities.len());
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut sorted = Vec::with_capacity(cities.len());
+
+        for (i, city) in cities.iter().enumerate() {
+            let name = fold(&city.name);
+
+            by_id.insert(city.id, i);
+            by_name.entry(name.clone()).or_default().push(i);
+            sorted.push((name, i));
+        }
+        sorted.sort();
+
+        CityList {
+            cities,
+            by_id,
+            by_name,
+            sorted,
+        }
+    }
+
+    /// Number of cities in the list.
+    pub fn len(&self) -> usize {
+        self.cities.len()
+    }
+
+    /// Whether the list contains no cities.
+    pub fn is_empty(&self) -> bool {
+        self.cities.is_empty()
+    }
+
+    /// Iterates over all the cities, in file order.
+    pub fn iter(&self) -> ::std::slice::Iter<'_, City> {
+        self.cities.iter()
+    }
+
+    /// Looks up a city by its ID.
+    pub fn get(&self, id: i32) -> Option<&City> {
+        self.by_id.get(&id).map(|&i| &self.cities[i])
+    }
+
+    /// Finds all the cities with the given name, ignoring case and diacritics,
+    /// optionally restricted to a country code.
+    pub fn search(&self, name: &str, country: Option<&str>) -> Vec<&City> {
+        self.by_name
+            .get(&fold(name))
+            .map(|indices| {
+                indices.iter()
+                    .map(|&i| &self.cities[i])
+                    .filter(|city| in_country(city, country))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns up to `limit` cities whose name starts with `prefix`, ignoring
+    /// case and diacritics, in alphabetical order.
+    pub fn autocomplete(&self, prefix: &str, country: Option<&str>, limit: usize) -> Vec<&City> {
+        let prefix = fold(prefix);
+        let start = self.sorted.partition_point(|(name, _)| name.as_str() < prefix.as_str());
+
+        self.sorted[start..]
+            .iter()
+            .take_while(|(name, _)| name.starts_with(&prefix))
+            .map(|&(_, i)| &self.cities[i])
+            .filter(|city| in_country(city, country))
+            .take(limit)
+            .collect()
+    }
+}
+
+fn in_country(city: &City, country: Option<&str>) -> bool {
+    country.is_none_or(|code| city.country.eq_ignore_ascii_case(code))
+}
+
+/// Normalizes a name for comparison: lowercase, without diacritics and
+/// with single spaces between words.
+pub fn fold(name: &str) -> String {
+    let mut folded = String::with_capacity(name.len());
+
+    for c in name.nfd().filter(|&c| !is_combining_mark(c)).flat_map(char::to_lowercase) {
+        match c {
+            'ß' => folded.push_str("ss"),
+            'æ' => folded.push_str("ae"),
+            'œ' => folded.push_str("oe"),
+            'ø' => folded.push('o'),
+            'ł' => folded.push('l'),
+            'đ' | 'ð' => folded.push('d'),
+            'ı' => folded.push('i'),
+            c if c.is_whitespace() => {
+                if !folded.is_empty() && !folded.ends_with(' ') {
+                    folded.push(' ');
+                }
+            }
+            c => folded.push(c),
+        }
+    }
+
+    let len = folded.trim_end().len();
+    folded.truncate(len);
+    folded
+}
@@ -0,0 +1,175 @@
+use std::cmp;
+use city::{fold, City, CityList};
+use {Error, Result};
+
+/// Minimum name similarity for a city to be considered a candidate.
+const MIN_SIMILARITY: f64 = 0.5;
+
+/// Maximum score difference between the two best candidates for a match to be ambiguous.
+const AMBIGUITY_MARGIN: f64 = 0.1;
+
+/// A city matching a free-text query, along with its confidence score.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    /// Matching city
+    pub city: City,
+    /// Confidence in the match, between 0 and 1
+    pub score: f64,
+}
+
+/// Free-text query split into its components.
+struct Query {
+    name: String,
+    region: Option<String>,
+    country: Option<String>,
+}
+
+impl Query {
+    /// Parses "city", "city, country" or "city, region, country". When only
+    /// two components are given, the second one may be either a country or a
+    /// region (e.g. "Paris, TX").
+    fn parse(text: &str) -> Query {
+        let parts: Vec<String> = text.split(',').map(fold).filter(|p| !p.is_empty()).collect();
+
+        let mut parts = parts.into_iter();
+        let name = parts.next().unwrap_or_default();
+
+        match (parts.next(), parts.next()) {
+            (Some(region), Some(country)) => {
+                Query {
+                    name,
+                    region: Some(region),
+                    country: Some(country),
+                }
+            }
+            (Some(either), None) => {
+                Query {
+                    name,
+                    region: Some(either.clone()),
+                    country: Some(either),
+                }
+            }
+            _ => {
+                Query {
+                    name,
+                    region: None,
+                    country: None,
+                }
+            }
+        }
+    }
+
+    /// Fraction of the given region and country that match the city. Queries
+    /// without either of them get a neutral score.
+    fn location_score(&self, city: &City) -> f64 {
+        let country = self.country.as_ref().map(|c| same_country(c, &city.country));
+        let region = self.region
+            .as_ref()
+            .map(|r| city.state.as_ref().is_some_and(|state| fold(state) == *r));
+
+        match (region, country) {
+            (None, None) => 0.5,
+            // A single component matches if it's either the region or the country
+            (Some(r), Some(c)) if self.region == self.country => if r || c { 1.0 } else { 0.0 },
+            (Some(r), Some(c)) => (r as u8 + c as u8) as f64 / 2.0,
+            (Some(m), None) | (None, Some(m)) => if m { 1.0 } else { 0.0 },
+        }
+    }
+}
+
+impl CityList {
+    /// Resolves a free-text "city, region, country" query into at most
+    /// `limit` candidates, best match first.
+    ///
+    /// Candidates are scored on the similarity of their name to the query,
+    /// their population and whether region and country match. Countries are
+    /// only matched by their ISO 3166 code, plus "UK" for GB: full names,
+    /// e.g. "France", match no country.
+    pub fn resolve(&self, text: &str, limit: usize) -> Vec<Candidate> {
+        let query = Query::parse(text);
+        let query_len = query.name.chars().count();
+
+        if query_len == 0 {
+            return Vec::new();
+        }
+
+        let max_edits = cmp::max(1, query_len / 3);
+
+        let mut found: Vec<Candidate> = self.sorted
+            .iter()
+            .filter_map(|&(ref name, i)| {
+                let name_len = name.chars().count();
+                if name_len.abs_diff(query_len) > max_edits {
+                    return None;
+                }
+
+                let edits = levenshtein(&query.name, name);
+                let similarity = 1.0 - edits as f64 / cmp::max(query_len, name_len) as f64;
+                if edits > max_edits || similarity < MIN_SIMILARITY {
+                    return None;
+                }
+
+                let city = &self.cities[i];
+                let population = city.population
+                    .map_or(0.0, |p| ((p as f64 + 1.0).log10() / 7.0).min(1.0));
+
+                Some(Candidate {
+                    city: city.clone(),
+                    score: 0.6 * similarity + 0.15 * population +
+                           0.25 * query.location_score(city),
+                })
+            })
+            .collect();
+
+        found.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.city.id.cmp(&b.city.id)));
+        found.truncate(limit);
+        found
+    }
+
+    /// Resolves a free-text query into a single city, failing if no city
+    /// matches or if several cities match almost equally well.
+    pub fn resolve_strict(&self, text: &str) -> Result<City> {
+        let mut found = self.resolve(text, 5);
+
+        if found.is_empty() {
+            return Err(Error::LocationNotFound(text.to_string()));
+        }
+        if found.len() > 1 && found[0].score - found[1].score < AMBIGUITY_MARGIN {
+            let best = found[0].score;
+            found.retain(|c| best - c.score < AMBIGUITY_MARGIN);
+            return Err(Error::AmbiguousLocation(found));
+        }
+
+        Ok(found.swap_remove(0).city)
+    }
+}
+
+/// Compares a folded country from a query with a country code. Only codes
+/// are supported, not country names.
+fn same_country(query: &str, code: &str) -> bool {
+    let query = match query {
+        "uk" => "gb",
+        query => query,
+    };
+
+    query.eq_ignore_ascii_case(code)
+}
+
+/// Number of single-character edits needed to turn `a` into `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..b.len() + 1).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = if ca == cb { diag } else { diag + 1 };
+            diag = row[j + 1];
+            row[j + 1] = cmp::min(cost, cmp::min(row[j], row[j + 1]) + 1);
+        }
+    }
+
+    row[b.len()]
+}
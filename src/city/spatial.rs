@@ -0,0 +1,200 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use city::{City, CityList};
+use geo::{self, EARTH_RADIUS_KM};
+
+/// Spatial index over a [CityList](struct.CityList.html), answering nearest
+/// and radius queries by great-circle distance.
+///
+/// Cities are stored in a k-d tree as points on the unit sphere, where the
+/// straight-line distance grows with the great-circle distance. This keeps
+/// queries correct near the poles and across the 180th meridian.
+pub struct SpatialIndex<'a> {
+    cities: &'a CityList,
+    tree: Vec<Node>,
+}
+
+struct Node {
+    point: [f64; 3],
+    city: usize,
+}
+
+/// Candidate in a k-nearest search, ordered by distance.
+struct Candidate {
+    dist: f64,
+    city: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Candidate) -> bool {
+        self.dist == other.dist
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        self.dist.total_cmp(&other.dist)
+    }
+}
+
+impl<'a> SpatialIndex<'a> {
+    /// Builds the index over all the cities in the list.
+    pub fn new(cities: &'a CityList) -> SpatialIndex<'a> {
+        let mut tree: Vec<Node> = cities.iter()
+            .enumerate()
+            .map(|(i, city)| {
+                Node {
+                    point: to_point(city.lat, city.lon),
+                    city: i,
+                }
+            })
+            .collect();
+
+        build(&mut tree, 0);
+
+        SpatialIndex { cities, tree }
+    }
+
+    /// Returns the `k` cities closest to the given point, along with their
+    /// distance [km], closest first.
+    pub fn nearest(&self, lat: f64, lon: f64, k: usize) -> Vec<(&'a City, f64)> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        self.search_nearest(&self.tree, 0, &to_point(lat, lon), k, &mut heap);
+
+        self.with_distances(lat, lon, heap.into_iter().map(|c| c.city))
+    }
+
+    /// Returns all the cities within `radius` [km] of the given point, along
+    /// with their distance [km], closest first.
+    pub fn within(&self, lat: f64, lon: f64, radius: f64) -> Vec<(&'a City, f64)> {
+        let angle = (radius / EARTH_RADIUS_KM).min(::std::f64::consts::PI);
+        let chord = 2.0 * (angle / 2.0).sin();
+
+        let mut found = Vec::new();
+        self.search_within(&self.tree, 0, &to_point(lat, lon), chord * chord, &mut found);
+
+        self.with_distances(lat, lon, found.into_iter())
+            .into_iter()
+            .filter(|&(_, dist)| dist <= radius)
+            .collect()
+    }
+
+    fn with_distances<I>(&self, lat: f64, lon: f64, cities: I) -> Vec<(&'a City, f64)>
+        where I: Iterator<Item = usize>
+    {
+        let mut found: Vec<(&'a City, f64)> = cities.map(|i| {
+                let city = &self.cities.cities[i];
+                (city, geo::distance(lat, lon, city.lat, city.lon))
+            })
+            .collect();
+
+        found.sort_by(|a, b| a.1.total_cmp(&b.1));
+        found
+    }
+
+    fn search_nearest(&self,
+                      tree: &[Node],
+                      depth: usize,
+                      target: &[f64; 3],
+                      k: usize,
+                      heap: &mut BinaryHeap<Candidate>) {
+        if tree.is_empty() {
+            return;
+        }
+
+        let mid = tree.len() / 2;
+        let node = &tree[mid];
+
+        heap.push(Candidate {
+            dist: squared_distance(&node.point, target),
+            city: node.city,
+        });
+        if heap.len() > k {
+            heap.pop();
+        }
+
+        let axis = depth % 3;
+        let diff = target[axis] - node.point[axis];
+        let (near, far) = if diff < 0.0 {
+            (&tree[..mid], &tree[mid + 1..])
+        } else {
+            (&tree[mid + 1..], &tree[..mid])
+        };
+
+        self.search_nearest(near, depth + 1, target, k, heap);
+        if heap.len() < k || heap.peek().is_some_and(|worst| diff * diff < worst.dist) {
+            self.search_nearest(far, depth + 1, target, k, heap);
+        }
+    }
+
+    fn search_within(&self,
+                     tree: &[Node],
+                     depth: usize,
+                     target: &[f64; 3],
+                     max: f64,
+                     found: &mut Vec<usize>) {
+        if tree.is_empty() {
+            return;
+        }
+
+        let mid = tree.len() / 2;
+        let node = &tree[mid];
+
+        if squared_distance(&node.point, target) <= max {
+            found.push(node.city);
+        }
+
+        let axis = depth % 3;
+        let diff = target[axis] - node.point[axis];
+
+        if diff < 0.0 || diff * diff <= max {
+            self.search_within(&tree[..mid], depth + 1, target, max, found);
+        }
+        if diff >= 0.0 || diff * diff <= max {
+            self.search_within(&tree[mid + 1..], depth + 1, target, max, found);
+        }
+    }
+}
+
+impl CityList {
+    /// Builds a spatial index over the cities, for nearest and radius queries.
+    pub fn spatial_index(&self) -> SpatialIndex<'_> {
+        SpatialIndex::new(self)
+    }
+}
+
+/// Arranges the nodes so that each subslice has its median on the splitting axis in the middle.
+fn build(tree: &mut [Node], depth: usize) {
+    if tree.len() <= 1 {
+        return;
+    }
+
+    let axis = depth % 3;
+    let mid = tree.len() / 2;
+    tree.select_nth_unstable_by(mid, |a, b| a.point[axis].total_cmp(&b.point[axis]));
+
+    let (left, right) = tree.split_at_mut(mid);
+    build(left, depth + 1);
+    build(&mut right[1..], depth + 1);
+}
+
+fn to_point(lat: f64, lon: f64) -> [f64; 3] {
+    let (lat, lon) = (lat.to_radians(), lon.to_radians());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+fn squared_distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum()
+}
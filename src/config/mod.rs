@@ -0,0 +1,330 @@
+//! Settings of a [WeatherHub](../struct.WeatherHub.html), loaded from a TOML
+//! file and environment variables.
+//!
+//! ```toml
+//! api_key = "YOUR_API_KEY"
+//! base_url = "http://api.openweathermap.org/data"
+//! units = "metric"        # standard, metric or imperial
+//! lang = "it"
+//! rate_limit = 60         # requests per minute
+//!
+//! [cache]
+//! ttl = 600               # seconds, 0 disables the cache
+//! capacity = 256          # responses
+//!
+//! [locations.home]
+//! type = "name"
+//! city = "London"
+//! country = "GB"
+//!
+//! [locations.office]
+//! type = "coords"
+//! lat = 43.7085
+//! lon = 10.4036
+//! ```
+//!
+//! Saved locations are [Location](../enum.Location.html)s, tagged by their
+//! `type`. All settings are optional, except for the API key when building a
+//! hub.
+//!
+//! Settings are taken, by order of precedence, from:
+//!
+//! 1. the environment: `OWM_API_KEY`, `OWM_BASE_URL`, `OWM_UNITS`, `OWM_LANG`,
+//!    `OWM_RATE_LIMIT` and `OWM_CACHE_TTL` (empty variables are ignored)
+//! 2. the configuration file, `$OWM_CONFIG` or else `config.toml` in the
+//!    [configuration directory](fn.config_dir.html)
+//! 3. for the API key only, the content of `api_key` in the configuration directory
+//!
+//! Builder methods called on the resulting hub, e.g. for command-line flags,
+//! take precedence over all of them.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use hyper;
+use {Error, Location, Result, Units, WeatherHub};
+
+/// Number of responses kept by the cache when the configuration doesn't say.
+pub const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+/// Validated settings of a hub.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    pub api_key: Option<String>,
+    pub base_url: Option<String>,
+    pub units: Option<Units>,
+    pub lang: Option<String>,
+    /// Maximum number of requests per minute
+    pub rate_limit: Option<u32>,
+    pub cache: Option<CacheConfig>,
+    /// Saved locations, by name
+    pub locations: BTreeMap<String, Location>,
+}
+
+/// Settings of the response cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// How long responses are kept
+    pub ttl: Duration,
+    /// Maximum number of responses kept
+    pub capacity: usize,
+}
+
+/// Content of a configuration file, before validation.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    api_key: Option<String>,
+    base_url: Option<String>,
+    units: Option<String>,
+    lang: Option<String>,
+    rate_limit: Option<i64>,
+    cache: Option<CacheFile>,
+    #[serde(default)]
+    locations: BTreeMap<String, Location>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CacheFile {
+    ttl: i64,
+    capacity: Option<i64>,
+}
+
+impl Config {
+    /// Parses and validates the content of a configuration file.
+    pub fn parse(text: &str) -> Result<Config> {
+        let file: File = toml::from_str(text)
+            .map_err(|err| Error::InvalidConfig(err.message().to_string() + &line(text, &err)))?;
+
+        let cache = match file.cache {
+            Some(cache) => {
+                let capacity = match cache.capacity {
+                    Some(capacity) => positive("cache.capacity", capacity)? as usize,
+                    None => DEFAULT_CACHE_CAPACITY,
+                };
+                self::cache("cache.ttl", cache.ttl, capacity)?
+            }
+            None => None,
+        };
+
+        let locations = file.locations
+            .into_iter()
+            .map(|(name, location)| Ok((name.clone(), location_of(&name, location)?)))
+            .collect::<Result<_>>()?;
+
+        Ok(Config {
+            api_key: file.api_key.map(|key| api_key("api_key", &key)).transpose()?,
+            base_url: file.base_url.map(|url| base_url("base_url", &url)).transpose()?,
+            units: file.units.map(|units| self::units("units", &units)).transpose()?,
+            lang: file.lang.map(|lang| self::lang("lang", &lang)).transpose()?,
+            rate_limit: file.rate_limit.map(|limit| positive("rate_limit", limit)).transpose()?,
+            cache,
+            locations,
+        })
+    }
+
+    /// Reads and validates a configuration file.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Config> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(Error::IoError)?;
+
+        Config::parse(&text).map_err(|err| match err {
+            Error::InvalidConfig(msg) => Error::InvalidConfig(format!("{}: {}", path.display(), msg)),
+            err => err,
+        })
+    }
+
+    /// Loads the configuration file and the environment variables, as
+    /// described in the [module documentation](index.html). A missing default
+    /// configuration file is not an error.
+    pub fn load() -> Result<Config> {
+        let config = match env::var_os("OWM_CONFIG") {
+            Some(path) => Config::from_path(path)?,
+            None => {
+                match config_dir().map(|dir| dir.join("config.toml")) {
+                    Some(ref path) if path.exists() => Config::from_path(path)?,
+                    _ => Config::default(),
+                }
+            }
+        };
+
+        Ok(config.with_env(|name| env::var(name).ok())?.with_key_file())
+    }
+
+    /// Takes a missing API key from the content of `api_key` in the
+    /// configuration directory, if any.
+    pub fn with_key_file(mut self) -> Config {
+        if self.api_key.is_none() {
+            let key = config_dir().and_then(|dir| fs::read_to_string(dir.join("api_key")).ok());
+            self.api_key = key.map(|key| key.trim().to_string()).filter(|key| !key.is_empty());
+        }
+        self
+    }
+
+    /// Overrides the settings with the environment variables returned by
+    /// `var`, e.g. `|name| std::env::var(name).ok()`.
+    pub fn with_env<F>(mut self, var: F) -> Result<Config>
+        where F: Fn(&str) -> Option<String>
+    {
+        let var = |name: &str| var(name).filter(|value| !value.is_empty());
+
+        if let Some(key) = var("OWM_API_KEY") {
+            self.api_key = Some(api_key("OWM_API_KEY", &key)?);
+        }
+        if let Some(url) = var("OWM_BASE_URL") {
+            self.base_url = Some(base_url("OWM_BASE_URL", &url)?);
+        }
+        if let Some(units) = var("OWM_UNITS") {
+            self.units = Some(self::units("OWM_UNITS", &units)?);
+        }
+        if let Some(lang) = var("OWM_LANG") {
+            self.lang = Some(self::lang("OWM_LANG", &lang)?);
+        }
+        if let Some(limit) = var("OWM_RATE_LIMIT") {
+            self.rate_limit = Some(positive("OWM_RATE_LIMIT", integer("OWM_RATE_LIMIT", &limit)?)?);
+        }
+        if let Some(ttl) = var("OWM_CACHE_TTL") {
+            let capacity = self.cache.map_or(DEFAULT_CACHE_CAPACITY, |cache| cache.capacity);
+            self.cache = cache("OWM_CACHE_TTL", integer("OWM_CACHE_TTL", &ttl)?, capacity)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Saved location of the given name.
+    pub fn location(&self, name: &str) -> Result<&Location> {
+        self.locations
+            .get(name)
+            .ok_or_else(|| Error::LocationNotFound(format!("no saved location named {}", name)))
+    }
+
+    /// Builds a hub with these settings, failing if there is no API key.
+    pub fn hub(&self, client: hyper::Client) -> Result<WeatherHub> {
+        let key = self.api_key.as_ref().ok_or_else(|| {
+            Error::InvalidConfig("missing api_key, set it in the configuration file or \
+                                  OWM_API_KEY"
+                .to_string())
+        })?;
+
+        let mut hub = WeatherHub::new(client, key);
+        if let Some(ref url) = self.base_url {
+            hub = hub.base_url(url);
+        }
+        if let Some(units) = self.units {
+            hub = hub.units(units);
+        }
+        if let Some(ref lang) = self.lang {
+            hub = hub.lang(lang);
+        }
+        if let Some(limit) = self.rate_limit {
+            hub = hub.rate_limit(limit);
+        }
+        if let Some(cache) = self.cache {
+            hub = hub.cache(cache.ttl, cache.capacity);
+        }
+
+        Ok(hub)
+    }
+}
+
+/// Directory of the configuration files: `$XDG_CONFIG_HOME/owm`, or else
+/// `~/.config/owm`.
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("owm"))
+}
+
+fn invalid(setting: &str, reason: &str) -> Error {
+    Error::InvalidConfig(format!("{}: {}", setting, reason))
+}
+
+/// Position of a TOML syntax error, e.g. " at line 3".
+fn line(text: &str, err: &toml::de::Error) -> String {
+    err.span()
+        .map(|span| format!(" at line {}", text[..span.start].lines().count().max(1)))
+        .unwrap_or_default()
+}
+
+fn api_key(setting: &str, key: &str) -> Result<String> {
+    match key.trim() {
+        "" => Err(invalid(setting, "empty API key")),
+        key => Ok(key.to_string()),
+    }
+}
+
+fn base_url(setting: &str, url: &str) -> Result<String> {
+    match ::url::Url::parse(url) {
+        Ok(ref parsed) if parsed.scheme() == "http" || parsed.scheme() == "https" => {
+            Ok(url.to_string())
+        }
+        _ => Err(invalid(setting, &format!("expected an http(s) URL, got \"{}\"", url))),
+    }
+}
+
+fn units(setting: &str, units: &str) -> Result<Units> {
+    match units {
+        "standard" => Ok(Units::Standard),
+        "metric" => Ok(Units::Metric),
+        "imperial" => Ok(Units::Imperial),
+        _ => {
+            Err(invalid(setting,
+                        &format!("expected standard, metric or imperial, got \"{}\"", units)))
+        }
+    }
+}
+
+fn lang(setting: &str, lang: &str) -> Result<String> {
+    if lang.chars().all(|c| c.is_ascii_alphabetic() || c == '_' || c == '-') {
+        Ok(lang.to_string())
+    } else {
+        Err(invalid(setting, &format!("invalid language code \"{}\"", lang)))
+    }
+}
+
+fn integer(setting: &str, value: &str) -> Result<i64> {
+    value.trim()
+        .parse()
+        .map_err(|_| invalid(setting, &format!("expected an integer, got \"{}\"", value)))
+}
+
+fn positive(setting: &str, value: i64) -> Result<u32> {
+    match value {
+        1..=0xffff_ffff => Ok(value as u32),
+        _ => Err(invalid(setting, &format!("expected a positive number, got {}", value))),
+    }
+}
+
+/// Cache settings for a TTL in seconds, where 0 disables the cache.
+fn cache(setting: &str, ttl: i64, capacity: usize) -> Result<Option<CacheConfig>> {
+    match ttl {
+        0 => Ok(None),
+        1..=0xffff_ffff => {
+            Ok(Some(CacheConfig {
+                ttl: Duration::from_secs(ttl as u64),
+                capacity,
+            }))
+        }
+        _ => Err(invalid(setting, &format!("expected a number of seconds, got {}", ttl))),
+    }
+}
+
+/// Validates a saved location, whose coordinates are already validated by
+/// deserialization.
+fn location_of(name: &str, location: Location) -> Result<Location> {
+    let setting = format!("locations.{}", name);
+
+    match location {
+        Location::Name { ref city, .. } if city.trim().is_empty() => {
+            Err(invalid(&setting, "empty city name"))
+        }
+        Location::PostalCode { ref code, .. } if code.trim().is_empty() => {
+            Err(invalid(&setting, "empty postal code"))
+        }
+        location => Ok(location),
+    }
+}
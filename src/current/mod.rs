@@ -1,5 +1,8 @@
 use ::*;
 
+/// Largest area [deg²] accepted by the server for a single bounding-box query.
+pub const MAX_BOX_AREA: f64 = 25.0;
+
 /// Query builder for the Current Weather API.
 pub struct CurrentWeatherQuery<'a> {
     hub: &'a WeatherHub,
@@ -38,8 +41,46 @@ impl<'a> CurrentWeatherQuery<'a> {
                                .build())
     }
 
+    /// Query current weather at any kind of location. As OWM only knows
+    /// states of the US, the country of a name with a state defaults to US.
+    pub fn at(mut self, location: &Location) -> Result<(hyper::client::Response, WeatherInfo)> {
+        match *location {
+            Location::Name { ref city, ref state, ref country } => {
+                let country = country.as_ref().map(String::as_str).or(state.as_ref().map(|_| "US"));
+                let q: Vec<&str> = Some(city.as_str())
+                    .into_iter()
+                    .chain(state.as_ref().map(String::as_str))
+                    .chain(country)
+                    .collect();
+
+                self.hub.run_query(self.builder
+                                       .method("weather")
+                                       .param("q", q.join(","))
+                                       .build())
+            }
+            Location::Id { id } => self.by_id(id),
+            Location::PostalCode { ref code, ref country } => {
+                self.by_postal_code(code, country.as_ref().map(String::as_str))
+            }
+            Location::Coords(ref point) => self.by_coords(point.lat() as f32, point.lon() as f32),
+        }
+    }
+
+    /// Query current weather by passing a free-text "city, region, country"
+    /// location, resolved offline against the given city list. Unlike
+    /// [by_name](#method.by_name), ambiguous locations are reported as errors
+    /// rather than letting the server pick one of the matching cities.
+    pub fn by_name_strict(self,
+                          location: &str,
+                          cities: &city::CityList)
+                          -> Result<(hyper::client::Response, WeatherInfo)> {
+        let city = cities.resolve_strict(location)?;
+        self.by_id(city.id)
+    }
+
     /// Query current weather by passing a city ID. API responds with exact result.
-    /// See http://bulk.openweathermap.org/sample/ for a list of city IDs.
+    /// See http://bulk.openweathermap.org/sample/ for a list of city IDs, which
+    /// can be searched offline with [CityList](../city/struct.CityList.html).
     pub fn by_id(mut self, id: i32) -> Result<(hyper::client::Response, WeatherInfo)> {
         self.hub.run_query(self.builder
                                .method("weather")
@@ -48,13 +89,23 @@ impl<'a> CurrentWeatherQuery<'a> {
     }
 
     /// Query current weather by passing a ZIP code and an optional country code.
-    pub fn by_zip_code(mut self,
+    /// Codes with leading zeros or letters require [by_postal_code](#method.by_postal_code).
+    pub fn by_zip_code(self,
                        zip: i32,
                        country: Option<&str>)
                        -> Result<(hyper::client::Response, WeatherInfo)> {
+        self.by_postal_code(&zip.to_string(), country)
+    }
+
+    /// Query current weather by passing a postal code, e.g. "SW1A 1AA" or
+    /// "01234", and an optional country code. Default country is US.
+    pub fn by_postal_code(mut self,
+                          code: &str,
+                          country: Option<&str>)
+                          -> Result<(hyper::client::Response, WeatherInfo)> {
         let q = match country {
-            None => zip.to_string(),
-            Some(code) => format!("{},{}", zip, code),
+            None => code.to_string(),
+            Some(country) => format!("{},{}", code, country),
         };
 
         self.hub.run_query(self.builder
@@ -68,34 +119,67 @@ impl<'a> CurrentWeatherQuery<'a> {
                      lat: f32,
                      lon: f32)
                      -> Result<(hyper::client::Response, WeatherInfo)> {
+        let (lat, lon) = validate(lat, lon)?;
+
         self.hub.run_query(self.builder
                                .method("weather")
-                               .param("lat", lat.to_string())
-                               .param("lon", lon.to_string())
+                               .param("lat", lat)
+                               .param("lon", lon)
                                .build())
     }
 
     /// Query current weather for cities within the defined rectangle specified
     /// by the bounding box using the given zoom. Server clustering of points
     /// can also be used.
-    pub fn by_bounds(mut self,
+    ///
+    /// Boxes crossing the 180th meridian are fetched with two requests, whose
+    /// results are merged. The response of the last request is returned.
+    pub fn by_bounds(self,
                      bbox: &BoundingBox,
                      zoom: i32,
                      cluster: bool)
                      -> Result<(hyper::client::Response, WeatherBoxAggregate)> {
-        let q = format!("{},{},{},{},{}",
-                        bbox.left,
-                        bbox.bottom,
-                        bbox.right,
-                        bbox.top,
-                        zoom);
+        let parts = BoundingBox::new(bbox.top, bbox.bottom, bbox.left, bbox.right)?.split();
+        self.by_bounds_parts(&parts, zoom, cluster)
+    }
 
-        self.hub.run_query(self.builder
-                               .method("box/city")
-                               .param("bbox", q)
-                               .param("cluster",
-                                      (if cluster { "yes" } else { "no" }).to_string())
-                               .build())
+    /// Query current weather for cities within a bounding box of any size, by
+    /// splitting it into tiles no larger than [MAX_BOX_AREA](constant.MAX_BOX_AREA.html).
+    ///
+    /// Tiles are fetched one after the other, respecting the hub's rate limit,
+    /// and cities found in several tiles are only reported once. The response
+    /// of the last request is returned.
+    pub fn by_bounds_tiled(self,
+                           bbox: &BoundingBox,
+                           zoom: i32,
+                           cluster: bool)
+                           -> Result<(hyper::client::Response, WeatherBoxAggregate)> {
+        let bbox = BoundingBox::new(bbox.top, bbox.bottom, bbox.left, bbox.right)?;
+        self.by_bounds_parts(&bbox.tiles(MAX_BOX_AREA.sqrt())?, zoom, cluster)
+    }
+
+    /// Fetches each box with a separate request and merges the results.
+    fn by_bounds_parts(mut self,
+                       parts: &[BoundingBox],
+                       zoom: i32,
+                       cluster: bool)
+                       -> Result<(hyper::client::Response, WeatherBoxAggregate)> {
+        self.builder
+            .method("box/city")
+            .param("cluster", (if cluster { "yes" } else { "no" }).to_string());
+
+        let query = self.builder.param("bbox", bbox_param(&parts[0], zoom)).build();
+        let (mut res, mut agg): (_, WeatherBoxAggregate) = self.hub.run_query(query)?;
+
+        for part in &parts[1..] {
+            let query = self.builder.param("bbox", bbox_param(part, zoom)).build();
+            let (next_res, next_agg) = self.hub.run_query(query)?;
+
+            agg.merge(next_agg);
+            res = next_res;
+        }
+
+        Ok((res, agg))
     }
 
     /// Query current weather for cities laid inside a circle specified by
@@ -106,10 +190,12 @@ impl<'a> CurrentWeatherQuery<'a> {
                      count: i32,
                      cluster: bool)
                      -> Result<(hyper::client::Response, WeatherAggregate)> {
+        let (lat, lon) = validate(lat, lon)?;
+
         self.hub.run_query(self.builder
                                .method("find")
-                               .param("lat", lat.to_string())
-                               .param("lon", lon.to_string())
+                               .param("lat", lat)
+                               .param("lon", lon)
                                .param("cnt", count.to_string())
                                .param("cluster",
                                       (if cluster { "yes" } else { "no" }).to_string())
@@ -117,3 +203,21 @@ impl<'a> CurrentWeatherQuery<'a> {
 
     }
 }
+
+/// Validates and normalizes a point, formatting its coordinates as query parameters.
+fn validate(lat: f32, lon: f32) -> Result<(String, String)> {
+    let pos = LatLon::new(lat as f64, lon as f64)?;
+    let lon = if (-180.0..180.0).contains(&lon) { lon } else { pos.lon() as f32 };
+
+    Ok((lat.to_string(), lon.to_string()))
+}
+
+/// Formats a box as the `bbox` query parameter.
+fn bbox_param(bbox: &BoundingBox, zoom: i32) -> String {
+    format!("{},{},{},{},{}",
+            bbox.left,
+            bbox.bottom,
+            bbox.right,
+            bbox.top,
+            zoom)
+}
@@ -1,20 +1,44 @@
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde::de::{DeserializeOwned, Error as DeError};
+use json;
+use serde_path_to_error::{self, Segment};
+use geo::LatLon;
+use {Error, Result};
+
+mod observations;
+mod wind;
+#[cfg(feature = "chrono")]
+mod time;
+
+pub use self::observations::{Field, Observations, Stats};
+pub use self::wind::CompassPoints;
+
 /// Contains the result of an aggregate query.
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct WeatherAggregate {
     /// Search accuracy. Possible values: "accurate", "like".
     pub message: Option<String>,
     /// HTTP status code for the request
+    #[serde(default, deserialize_with = "de_code")]
     pub cod: Option<i32>,
     /// Number of items in the list
     pub count: Option<i32>,
     /// List of weather info
     pub list: Option<Vec<WeatherInfo>>,
+    /// Fields discarded while decoding in lenient mode
+    #[serde(skip)]
+    pub warnings: Vec<DecodeWarning>,
+    /// Fields not covered by this struct, preserved as sent by the server
+    #[serde(flatten)]
+    pub extra: HashMap<String, json::Value>,
 }
 
 /// Contains the result of a bounding-box query.
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct WeatherBoxAggregate {
     /// HTTP status code for the request
+    #[serde(default, deserialize_with = "de_code")]
     pub cod: Option<i32>,
     /// Time elapsed server-side to handle the request
     pub calctime: Option<f32>,
@@ -22,6 +46,39 @@ pub struct WeatherBoxAggregate {
     pub cnt: Option<i32>,
     /// List of weather info
     pub list: Option<Vec<WeatherInfo>>,
+    /// Fields discarded while decoding in lenient mode
+    #[serde(skip)]
+    pub warnings: Vec<DecodeWarning>,
+    /// Fields not covered by this struct, preserved as sent by the server
+    #[serde(flatten)]
+    pub extra: HashMap<String, json::Value>,
+}
+
+impl WeatherBoxAggregate {
+    /// Appends the cities of another result to this one, skipping the ones
+    /// already present. Processing times are added up.
+    pub fn merge(&mut self, other: WeatherBoxAggregate) {
+        let list = self.list.get_or_insert_with(Vec::new);
+        let mut known: HashSet<i64> = list.iter().filter_map(|info| info.id).collect();
+
+        for info in other.list.unwrap_or_default() {
+            let new = match info.id {
+                Some(id) => known.insert(id),
+                None => true,
+            };
+            if new {
+                list.push(info);
+            }
+        }
+
+        self.cnt = Some(list.len() as i32);
+        self.cod = self.cod.or(other.cod);
+        self.calctime = match (self.calctime, other.calctime) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        self.warnings.extend(other.warnings);
+    }
 }
 
 /// Contains all the weather-related information.
@@ -35,6 +92,8 @@ pub struct WeatherInfo {
     pub base: Option<String>,
     /// General weather parameters
     pub main: Option<Main>,
+    /// Visibility [m], capped by the API at 10 km
+    pub visibility: Option<i32>,
     /// Wind-related information
     pub wind: Option<Wind>,
     /// Cloud-related information
@@ -47,21 +106,42 @@ pub struct WeatherInfo {
     pub dt: Option<i64>,
     /// Internal parameter
     pub sys: Option<Sys>,
+    /// Shift in seconds from UTC of the location's timezone
+    pub timezone: Option<i32>,
     /// City ID
     pub id: Option<i64>,
     /// City name
     pub name: Option<String>,
     /// Internal parameter
+    #[serde(default, deserialize_with = "de_code")]
     pub cod: Option<i32>,
+    /// Fields discarded while decoding in lenient mode
+    #[serde(skip)]
+    pub warnings: Vec<DecodeWarning>,
+    /// Fields not covered by this struct, preserved as sent by the server
+    #[serde(flatten)]
+    pub extra: HashMap<String, json::Value>,
 }
 
 /// Contains the geographic coordinates of the location.
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct Coordinates {
     /// Longitude
+    #[serde(alias = "Lon")]
     pub lon: Option<f32>,
     /// Latitude
+    #[serde(alias = "Lat")]
     pub lat: Option<f32>,
+    /// Fields not covered by this struct, preserved as sent by the server
+    #[serde(flatten)]
+    pub extra: HashMap<String, json::Value>,
+}
+
+impl Coordinates {
+    /// Validated location, if both latitude and longitude are available.
+    pub fn lat_lon(&self) -> Option<LatLon> {
+        LatLon::new(f64::from(self.lat?), f64::from(self.lon?)).ok()
+    }
 }
 
 /// Represents OpenWeatherMap's weather condition codes.
@@ -76,6 +156,9 @@ pub struct Weather {
     pub description: Option<String>,
     /// Weather icon ID
     pub icon: Option<String>,
+    /// Fields not covered by this struct, preserved as sent by the server
+    #[serde(flatten)]
+    pub extra: HashMap<String, json::Value>,
 }
 
 /// Contains weather information not tied to particular weather conditions.
@@ -83,8 +166,10 @@ pub struct Weather {
 pub struct Main {
     /// Current temperature. Unit Default: [K], Metric: [°C], Imperial: [°F]
     pub temp: Option<f32>,
+    /// Temperature accounting for the human perception of weather. Same units as temp.
+    pub feels_like: Option<f32>,
     /// Atmospheric pressure [hPa] (at sea level, if there is no sea_level or grnd_level data)
-    pub pressure: Option<i32>,
+    pub pressure: Option<f32>,
     /// Humidity [%]
     pub humidity: Option<i32>,
     /// Minimum temperature at the moment (deviation from current temperature, significant for large areas)
@@ -92,9 +177,12 @@ pub struct Main {
     /// Maximum temperature at the moment (deviation from current temperature, significant for large areas)
     pub temp_max: Option<f32>,
     /// Atmospheric pressure on the sea level [hPa]
-    pub sea_level: Option<i32>,
+    pub sea_level: Option<f32>,
     /// Atmospheric pressure on the ground level [hPa]
-    pub grnd_level: Option<i32>,
+    pub grnd_level: Option<f32>,
+    /// Fields not covered by this struct, preserved as sent by the server
+    #[serde(flatten)]
+    pub extra: HashMap<String, json::Value>,
 }
 
 /// Contains wind-related information.
@@ -103,32 +191,51 @@ pub struct Wind {
     /// Wind speed. Unit default: [m/s], Metric: [m/s], Imperial: [miles/h]
     pub speed: Option<f32>,
     /// Wind direction [deg] (meteorological)
-    pub deg: Option<i32>,
-    /// Wind gust. Same units as speed.AsMut
+    pub deg: Option<f32>,
+    /// Wind gust. Same units as speed.
     pub gust: Option<f32>,
+    /// Fields not covered by this struct, preserved as sent by the server
+    #[serde(flatten)]
+    pub extra: HashMap<String, json::Value>,
 }
 
 /// Contains cloud-related information.
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct Clouds {
     /// Cloudiness [%]
+    #[serde(alias = "today")]
     pub all: Option<i32>,
+    /// Fields not covered by this struct, preserved as sent by the server
+    #[serde(flatten)]
+    pub extra: HashMap<String, json::Value>,
 }
 
 /// Contains rain-related information.
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct Rain {
-    /// Rain volume for the last 3 hours
+    /// Rain volume for the last hour [mm]
+    #[serde(rename="1h")]
+    pub one_hour: Option<f32>,
+    /// Rain volume for the last 3 hours [mm]
     #[serde(rename="3h")]
-    pub three_hours: Option<i32>,
+    pub three_hours: Option<f32>,
+    /// Fields not covered by this struct, preserved as sent by the server
+    #[serde(flatten)]
+    pub extra: HashMap<String, json::Value>,
 }
 
 /// Contains snow-related information.
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct Snow {
-    /// Snow volume for the last 3 hours
+    /// Snow volume for the last hour [mm]
+    #[serde(rename="1h")]
+    pub one_hour: Option<f32>,
+    /// Snow volume for the last 3 hours [mm]
     #[serde(rename="3h")]
-    pub three_hours: Option<i32>,
+    pub three_hours: Option<f32>,
+    /// Fields not covered by this struct, preserved as sent by the server
+    #[serde(flatten)]
+    pub extra: HashMap<String, json::Value>,
 }
 
 /// Contains internal API parameters.
@@ -147,13 +254,157 @@ pub struct Sys {
     pub sunrise: Option<i64>,
     /// Sunset time, Unix, UTC
     pub sunset: Option<i64>,
+    /// Fields not covered by this struct, preserved as sent by the server
+    #[serde(flatten)]
+    pub extra: HashMap<String, json::Value>,
 }
 
 /// Represents an error message sent by the API server in response to a bad request.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
     /// HTTP status code for the requested resource
+    #[serde(default, deserialize_with = "de_code")]
     pub cod: Option<i32>,
     /// Human-readable error message
     pub message: Option<String>,
+    /// Fields not covered by this struct, preserved as sent by the server
+    #[serde(flatten)]
+    pub extra: HashMap<String, json::Value>,
+}
+
+/// Status codes are sent either as numbers or as strings, depending on the endpoint.
+fn de_code<'de, D>(deserializer: D) -> ::std::result::Result<Option<i32>, D::Error>
+    where D: Deserializer<'de>
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Code {
+        Int(i32),
+        Str(String),
+    }
+
+    match Option::<Code>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Code::Int(code)) => Ok(Some(code)),
+        Some(Code::Str(code)) => code.parse().map(Some).map_err(D::Error::custom),
+    }
+}
+
+/// Strategy used to decode the responses sent by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecodeMode {
+    /// Any malformed field fails the whole response. This is the default.
+    #[default]
+    Strict,
+    /// Malformed fields are discarded and recorded as warnings.
+    Lenient,
+}
+
+/// Describes a field that was discarded while decoding in lenient mode.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodeWarning {
+    /// Location of the field in the response, e.g. `list[2].main.humidity`
+    pub path: String,
+    /// Reason why the field could not be decoded
+    pub message: String,
+}
+
+/// Implemented by the top-level responses, which carry lenient decoding warnings.
+pub trait ApiResponse: DeserializeOwned {
+    fn warnings_mut(&mut self) -> &mut Vec<DecodeWarning>;
+}
+
+impl ApiResponse for WeatherAggregate {
+    fn warnings_mut(&mut self) -> &mut Vec<DecodeWarning> {
+        &mut self.warnings
+    }
+}
+
+impl ApiResponse for WeatherBoxAggregate {
+    fn warnings_mut(&mut self) -> &mut Vec<DecodeWarning> {
+        &mut self.warnings
+    }
+}
+
+impl ApiResponse for WeatherInfo {
+    fn warnings_mut(&mut self) -> &mut Vec<DecodeWarning> {
+        &mut self.warnings
+    }
+}
+
+/// Converts a response into a JSON value. Unlike `serde_json::to_value`,
+/// floats keep their shortest decimal form, e.g. 10.4036 rather than
+/// 10.403599739074707.
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> json::Value {
+    json::to_string(value)
+        .and_then(|text| json::from_str(&text))
+        .expect("responses are always representable as JSON")
+}
+
+/// Decodes a JSON response using the given mode.
+///
+/// In lenient mode, every field that fails to decode is removed from the
+/// response and decoding is retried, so that it shows up as `None`.
+pub fn decode<D>(body: &str, mode: DecodeMode) -> Result<D>
+    where D: ApiResponse
+{
+    if mode == DecodeMode::Strict {
+        return json::from_str(body).map_err(|err| Error::JsonDecodeError(body.to_string(), err));
+    }
+
+    let mut value: json::Value = match json::from_str(body) {
+        Ok(value) => value,
+        Err(err) => return Err(Error::JsonDecodeError(body.to_string(), err)),
+    };
+    let mut warnings = Vec::new();
+
+    loop {
+        match serde_path_to_error::deserialize::<_, D>(value.clone()) {
+            Ok(mut decoded) => {
+                decoded.warnings_mut().extend(warnings);
+                return Ok(decoded);
+            }
+            Err(err) => {
+                let path = err.path().clone();
+                let err = err.into_inner();
+
+                let segments: Vec<Segment> = path.iter().cloned().collect();
+
+                if !discard(&mut value, &segments) {
+                    return Err(Error::JsonDecodeError(body.to_string(), err));
+                }
+                warnings.push(DecodeWarning {
+                    path: path.to_string(),
+                    message: err.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Removes the element at `path` from `value`. Returns false if there was nothing to remove.
+fn discard(value: &mut json::Value, path: &[Segment]) -> bool {
+    let (segment, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return false,
+    };
+
+    match (segment, value) {
+        (Segment::Map { key }, json::Value::Object(map)) => {
+            if rest.is_empty() {
+                map.remove(key).is_some()
+            } else {
+                map.get_mut(key).is_some_and(|child| discard(child, rest))
+            }
+        }
+        (&Segment::Seq { index }, json::Value::Array(seq)) => {
+            if rest.is_empty() && index < seq.len() {
+                seq.remove(index);
+                true
+            } else {
+                seq.get_mut(index).is_some_and(|child| discard(child, rest))
+            }
+        }
+        _ => false,
+    }
 }
@@ -0,0 +1,210 @@
+use std::cmp::Ordering;
+use std::slice;
+use data::{WeatherAggregate, WeatherBoxAggregate, WeatherInfo};
+use geo::LatLon;
+
+/// Numeric field of an observation. Values are expressed in the units the
+/// observations were fetched in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Temperature,
+    FeelsLike,
+    Pressure,
+    Humidity,
+    WindSpeed,
+    WindGust,
+    /// Eastward component of the wind
+    WindU,
+    /// Northward component of the wind
+    WindV,
+    Cloudiness,
+    Visibility,
+    /// Rain volume over the last hour
+    Rain,
+    /// Snow volume over the last hour
+    Snow,
+}
+
+impl Field {
+    /// Value of the field for the given observation, if available.
+    pub fn value(&self, info: &WeatherInfo) -> Option<f64> {
+        match *self {
+            Field::Temperature => info.main.as_ref()?.temp.map(f64::from),
+            Field::FeelsLike => info.main.as_ref()?.feels_like.map(f64::from),
+            Field::Pressure => info.main.as_ref()?.pressure.map(f64::from),
+            Field::Humidity => info.main.as_ref()?.humidity.map(f64::from),
+            Field::WindSpeed => info.wind.as_ref()?.speed.map(f64::from),
+            Field::WindGust => info.wind.as_ref()?.gust.map(f64::from),
+            Field::WindU => info.wind.as_ref()?.components().map(|(u, _)| f64::from(u)),
+            Field::WindV => info.wind.as_ref()?.components().map(|(_, v)| f64::from(v)),
+            Field::Cloudiness => info.clouds.as_ref()?.all.map(f64::from),
+            Field::Visibility => info.visibility.map(f64::from),
+            Field::Rain => info.rain.as_ref()?.one_hour.map(f64::from),
+            Field::Snow => info.snow.as_ref()?.one_hour.map(f64::from),
+        }
+    }
+}
+
+/// Summary statistics of a field over several observations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    /// Number of observations providing the field
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    /// Population standard deviation
+    pub std_dev: f64,
+    sorted: Vec<f64>,
+}
+
+impl Stats {
+    /// Computes the statistics of the given values, ignoring NaNs. Returns
+    /// `None` if there are no values left.
+    pub fn new<I: IntoIterator<Item = f64>>(values: I) -> Option<Stats> {
+        let mut sorted: Vec<f64> = values.into_iter().filter(|v| !v.is_nan()).collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(f64::total_cmp);
+
+        let count = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / count as f64;
+        let variance = sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count as f64;
+
+        let mut stats = Stats {
+            count,
+            min: sorted[0],
+            max: sorted[count - 1],
+            mean,
+            median: 0.0,
+            std_dev: variance.sqrt(),
+            sorted,
+        };
+        stats.median = stats.percentile(50.0);
+
+        Some(stats)
+    }
+
+    /// Value below which the given percentage of the values fall,
+    /// interpolating linearly between the closest ones.
+    pub fn percentile(&self, percent: f64) -> f64 {
+        let rank = percent.clamp(0.0, 100.0) / 100.0 * (self.count - 1) as f64;
+        let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+
+        self.sorted[below] + (self.sorted[above] - self.sorted[below]) * (rank - below as f64)
+    }
+}
+
+/// Implemented by the responses listing the weather of several cities.
+///
+/// Observations lacking the field an operation works on are ignored, or
+/// sorted last.
+pub trait Observations {
+    /// Weather of the cities in the response.
+    fn observations(&self) -> &[WeatherInfo];
+
+    /// Iterates over the observations.
+    fn iter(&self) -> slice::Iter<'_, WeatherInfo> {
+        self.observations().iter()
+    }
+
+    /// Observations sorted by increasing value of the field.
+    fn sorted_by(&self, field: Field) -> Vec<&WeatherInfo> {
+        let mut sorted: Vec<&WeatherInfo> = self.observations().iter().collect();
+        sorted.sort_by(|a, b| match (field.value(a), field.value(b)) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => a.is_none().cmp(&b.is_none()),
+        });
+
+        sorted
+    }
+
+    /// Observations whose value of the field satisfies the predicate.
+    fn filter_by<P>(&self, field: Field, predicate: P) -> Vec<&WeatherInfo>
+        where P: Fn(f64) -> bool
+    {
+        self.observations()
+            .iter()
+            .filter(|info| field.value(info).is_some_and(&predicate))
+            .collect()
+    }
+
+    /// Observation closest to the point, along with its distance [km].
+    fn nearest(&self, point: &LatLon) -> Option<(&WeatherInfo, f64)> {
+        self.observations()
+            .iter()
+            .filter_map(|info| Some((info, point.distance(&info.coord.as_ref()?.lat_lon()?))))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// First observation with the lowest value of the field.
+    fn lowest(&self, field: Field) -> Option<&WeatherInfo> {
+        extreme(self.observations(), field, Ordering::Less)
+    }
+
+    /// First observation with the highest value of the field.
+    fn highest(&self, field: Field) -> Option<&WeatherInfo> {
+        extreme(self.observations(), field, Ordering::Greater)
+    }
+
+    /// Observation with the highest temperature.
+    fn hottest(&self) -> Option<&WeatherInfo> {
+        self.highest(Field::Temperature)
+    }
+
+    /// Observation with the lowest temperature.
+    fn coldest(&self) -> Option<&WeatherInfo> {
+        self.lowest(Field::Temperature)
+    }
+
+    /// Observation with the highest wind speed.
+    fn windiest(&self) -> Option<&WeatherInfo> {
+        self.highest(Field::WindSpeed)
+    }
+
+    /// Summary statistics of the field.
+    fn stats(&self, field: Field) -> Option<Stats> {
+        Stats::new(self.observations().iter().filter_map(|info| field.value(info)))
+    }
+
+    /// Value of the field below which the given percentage of the observations fall.
+    fn percentile(&self, field: Field, percent: f64) -> Option<f64> {
+        self.stats(field).map(|stats| stats.percentile(percent))
+    }
+}
+
+impl Observations for WeatherAggregate {
+    fn observations(&self) -> &[WeatherInfo] {
+        self.list.as_ref().map_or(&[], Vec::as_slice)
+    }
+}
+
+impl Observations for WeatherBoxAggregate {
+    fn observations(&self) -> &[WeatherInfo] {
+        self.list.as_ref().map_or(&[], Vec::as_slice)
+    }
+}
+
+impl Observations for [WeatherInfo] {
+    fn observations(&self) -> &[WeatherInfo] {
+        self
+    }
+}
+
+/// First observation whose value of the field compares to all the others
+/// as `wanted`, or equal.
+fn extreme(observations: &[WeatherInfo], field: Field, wanted: Ordering) -> Option<&WeatherInfo> {
+    let mut best: Option<(&WeatherInfo, f64)> = None;
+
+    for info in observations {
+        if let Some(value) = field.value(info) {
+            if best.is_none_or(|(_, best)| value.total_cmp(&best) == wanted) {
+                best = Some((info, value));
+            }
+        }
+    }
+
+    best.map(|(info, _)| info)
+}
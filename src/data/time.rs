@@ -0,0 +1,52 @@
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use data::{Sys, WeatherInfo};
+
+fn utc(timestamp: Option<i64>) -> Option<DateTime<Utc>> {
+    timestamp.and_then(|ts| Utc.timestamp_opt(ts, 0).single())
+}
+
+impl WeatherInfo {
+    /// Time of data calculation, UTC.
+    pub fn dt_utc(&self) -> Option<DateTime<Utc>> {
+        utc(self.dt)
+    }
+
+    /// Time of data calculation, in the local time of the location.
+    pub fn dt_local(&self) -> Option<DateTime<FixedOffset>> {
+        self.localize(self.dt_utc())
+    }
+
+    /// Sunrise time, in the local time of the location.
+    pub fn sunrise_local(&self) -> Option<DateTime<FixedOffset>> {
+        self.localize(self.sys.as_ref().and_then(Sys::sunrise_utc))
+    }
+
+    /// Sunset time, in the local time of the location.
+    pub fn sunset_local(&self) -> Option<DateTime<FixedOffset>> {
+        self.localize(self.sys.as_ref().and_then(Sys::sunset_utc))
+    }
+
+    /// Offset from UTC of the location's timezone.
+    pub fn utc_offset(&self) -> Option<FixedOffset> {
+        self.timezone.and_then(FixedOffset::east_opt)
+    }
+
+    fn localize(&self, time: Option<DateTime<Utc>>) -> Option<DateTime<FixedOffset>> {
+        match (time, self.utc_offset()) {
+            (Some(time), Some(offset)) => Some(time.with_timezone(&offset)),
+            _ => None,
+        }
+    }
+}
+
+impl Sys {
+    /// Sunrise time, UTC.
+    pub fn sunrise_utc(&self) -> Option<DateTime<Utc>> {
+        utc(self.sunrise)
+    }
+
+    /// Sunset time, UTC.
+    pub fn sunset_utc(&self) -> Option<DateTime<Utc>> {
+        utc(self.sunset)
+    }
+}
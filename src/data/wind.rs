@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use data::Wind;
+use Units;
+
+/// Number of points of the compass rose used to name a wind direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompassPoints {
+    Eight,
+    Sixteen,
+    ThirtyTwo,
+}
+
+const COMPASS: [&str; 32] = ["N", "NbE", "NNE", "NEbN", "NE", "NEbE", "ENE", "EbN", "E", "EbS",
+                             "ESE", "SEbE", "SE", "SEbS", "SSE", "SbE", "S", "SbW", "SSW",
+                             "SWbS", "SW", "SWbW", "WSW", "WbS", "W", "WbN", "WNW", "NWbW", "NW",
+                             "NWbN", "NNW", "NbW"];
+
+/// Upper bounds [m/s] of Beaufort numbers 0 to 11.
+const BEAUFORT: [f32; 12] = [0.5, 1.6, 3.4, 5.5, 8.0, 10.8, 13.9, 17.2, 20.8, 24.5, 28.5, 32.7];
+
+const BEAUFORT_DESCRIPTION: [&str; 13] = ["Calm",
+                                          "Light air",
+                                          "Light breeze",
+                                          "Gentle breeze",
+                                          "Moderate breeze",
+                                          "Fresh breeze",
+                                          "Strong breeze",
+                                          "Near gale",
+                                          "Gale",
+                                          "Strong gale",
+                                          "Storm",
+                                          "Violent storm",
+                                          "Hurricane force"];
+
+impl Wind {
+    /// Builds a wind from its eastward (u) and northward (v) components.
+    /// Calm winds are reported as blowing from the north.
+    pub fn from_components(u: f32, v: f32) -> Wind {
+        let deg = if u == 0.0 && v == 0.0 {
+            0.0
+        } else {
+            ((-u).atan2(-v).to_degrees() + 360.0) % 360.0
+        };
+
+        Wind {
+            speed: Some(u.hypot(v)),
+            deg: Some(deg),
+            gust: None,
+            extra: HashMap::new(),
+        }
+    }
+
+    /// Name of the compass point the wind is blowing from, e.g. "NNE".
+    pub fn compass(&self, points: CompassPoints) -> Option<&'static str> {
+        let step = match points {
+            CompassPoints::Eight => 4,
+            CompassPoints::Sixteen => 2,
+            CompassPoints::ThirtyTwo => 1,
+        };
+        let sector = 360.0 / (COMPASS.len() / step) as f32;
+        let index = (self.deg?.rem_euclid(360.0) / sector).round() as usize * step;
+
+        Some(COMPASS[index % COMPASS.len()])
+    }
+
+    /// Beaufort number of the wind speed, which must be expressed in the given units.
+    pub fn beaufort(&self, units: Units) -> Option<u8> {
+        let speed = match units {
+            Units::Standard | Units::Metric => self.speed?,
+            Units::Imperial => self.speed? * 0.44704,
+        };
+
+        Some(BEAUFORT.iter().take_while(|&&max| speed >= max).count() as u8)
+    }
+
+    /// Description of the Beaufort number of the wind speed, e.g. "Gentle breeze".
+    pub fn beaufort_description(&self, units: Units) -> Option<&'static str> {
+        self.beaufort(units).map(|n| BEAUFORT_DESCRIPTION[n as usize])
+    }
+
+    /// Eastward (u) and northward (v) components of the wind. Same units as speed.
+    pub fn components(&self) -> Option<(f32, f32)> {
+        let (speed, rad) = (self.speed?, self.deg?.to_radians());
+
+        Some((-speed * rad.sin(), -speed * rad.cos()))
+    }
+
+    /// Averages the given samples as vectors. Samples lacking either speed or
+    /// direction are ignored, and the resulting gust is the highest one.
+    pub fn average(samples: &[Wind]) -> Option<Wind> {
+        let components: Vec<(f32, f32)> = samples.iter().filter_map(Wind::components).collect();
+
+        if components.is_empty() {
+            return None;
+        }
+
+        let n = components.len() as f32;
+        let (u, v) = components.iter().fold((0.0, 0.0), |(su, sv), &(u, v)| (su + u, sv + v));
+
+        let mut wind = Wind::from_components(u / n, v / n);
+        wind.gust = samples.iter().filter_map(|w| w.gust).fold(None, |max, g| {
+            Some(max.map_or(g, |max: f32| max.max(g)))
+        });
+
+        Some(wind)
+    }
+}
@@ -0,0 +1,173 @@
+//! Meteorological quantities derived from the raw observations.
+//!
+//! The free functions in this module work in SI units (°C, m/s). The
+//! [Derived](struct.Derived.html) view takes care of converting from and to
+//! the [Units](../enum.Units.html) a `WeatherInfo` was fetched in.
+
+use data::WeatherInfo;
+use Units;
+
+/// View over a `WeatherInfo` computing derived metrics in the units it was fetched in.
+///
+/// Every metric returns `None` if any of its inputs is missing from the observation.
+pub struct Derived<'a> {
+    info: &'a WeatherInfo,
+    units: Units,
+}
+
+impl WeatherInfo {
+    /// Provides access to the derived metrics for this observation, which
+    /// must have been fetched in the given units.
+    pub fn derived(&self, units: Units) -> Derived<'_> {
+        Derived::new(self, units)
+    }
+}
+
+impl<'a> Derived<'a> {
+    pub fn new(info: &'a WeatherInfo, units: Units) -> Derived<'a> {
+        Derived { info, units }
+    }
+
+    /// Dew point. Same units as temp.
+    pub fn dew_point(&self) -> Option<f32> {
+        Some(self.to_units(dew_point(self.temp()?, self.humidity()?)))
+    }
+
+    /// Heat index, as computed by the US National Weather Service. Same units as temp.
+    pub fn heat_index(&self) -> Option<f32> {
+        Some(self.to_units(heat_index(self.temp()?, self.humidity()?)))
+    }
+
+    /// Wind chill, as computed by the US National Weather Service and
+    /// Environment Canada. Same units as temp.
+    pub fn wind_chill(&self) -> Option<f32> {
+        Some(self.to_units(wind_chill(self.temp()?, self.wind_speed()?)))
+    }
+
+    /// Humidex, as computed by Environment Canada. Same units as temp.
+    pub fn humidex(&self) -> Option<f32> {
+        Some(self.to_units(humidex(self.temp()?, self.humidity()?)))
+    }
+
+    /// Apparent temperature, as computed by the Australian Bureau of
+    /// Meteorology. Same units as temp.
+    pub fn apparent_temperature(&self) -> Option<f32> {
+        let temp = apparent_temperature(self.temp()?, self.humidity()?, self.wind_speed()?);
+        Some(self.to_units(temp))
+    }
+
+    /// Absolute humidity [g/m³]
+    pub fn absolute_humidity(&self) -> Option<f32> {
+        Some(absolute_humidity(self.temp()?, self.humidity()?))
+    }
+
+    /// Air temperature [°C]
+    fn temp(&self) -> Option<f32> {
+        let temp = self.info.main.as_ref()?.temp?;
+
+        Some(match self.units {
+            Units::Standard => temp - 273.15,
+            Units::Metric => temp,
+            Units::Imperial => (temp - 32.0) * 5.0 / 9.0,
+        })
+    }
+
+    /// Relative humidity [%]
+    fn humidity(&self) -> Option<f32> {
+        self.info.main.as_ref()?.humidity.map(|h| h as f32)
+    }
+
+    /// Wind speed [m/s]
+    fn wind_speed(&self) -> Option<f32> {
+        let speed = self.info.wind.as_ref()?.speed?;
+
+        Some(match self.units {
+            Units::Standard | Units::Metric => speed,
+            Units::Imperial => speed * 0.44704,
+        })
+    }
+
+    fn to_units(&self, temp: f32) -> f32 {
+        match self.units {
+            Units::Standard => temp + 273.15,
+            Units::Metric => temp,
+            Units::Imperial => temp * 9.0 / 5.0 + 32.0,
+        }
+    }
+}
+
+/// Saturation vapour pressure [hPa] over water at the given temperature [°C].
+fn vapour_pressure(temp: f32) -> f32 {
+    6.112 * (17.67 * temp / (temp + 243.5)).exp()
+}
+
+/// Dew point [°C] from temperature [°C] and relative humidity [%], using the
+/// Magnus formula.
+pub fn dew_point(temp: f32, humidity: f32) -> f32 {
+    let (a, b) = (17.62, 243.12);
+    let gamma = (humidity.max(1.0) / 100.0).ln() + a * temp / (b + temp);
+
+    b * gamma / (a - gamma)
+}
+
+/// Heat index [°C] from temperature [°C] and relative humidity [%].
+pub fn heat_index(temp: f32, humidity: f32) -> f32 {
+    let t = f64::from(temp) * 9.0 / 5.0 + 32.0;
+    let rh = f64::from(humidity);
+
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+
+    let hi = if (simple + t) / 2.0 < 80.0 {
+        simple
+    } else {
+        let mut hi = -42.379 + 2.04901523 * t + 10.14333127 * rh - 0.22475541 * t * rh -
+                     0.00683783 * t * t - 0.05481717 * rh * rh +
+                     0.00122874 * t * t * rh + 0.00085282 * t * rh * rh -
+                     0.00000199 * t * t * rh * rh;
+
+        if rh < 13.0 && t > 80.0 && t < 112.0 {
+            hi -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+        } else if rh > 85.0 && t > 80.0 && t < 87.0 {
+            hi += (rh - 85.0) / 10.0 * (87.0 - t) / 5.0;
+        }
+        hi
+    };
+
+    ((hi - 32.0) * 5.0 / 9.0) as f32
+}
+
+/// Wind chill [°C] from temperature [°C] and wind speed [m/s].
+///
+/// The index is only defined for temperatures up to 10 °C and wind speeds
+/// above 4.8 km/h: outside that range, the air temperature is returned.
+pub fn wind_chill(temp: f32, wind_speed: f32) -> f32 {
+    let v = wind_speed * 3.6;
+
+    if temp > 10.0 || v <= 4.8 {
+        return temp;
+    }
+
+    let v = v.powf(0.16);
+    13.12 + 0.6215 * temp - 11.37 * v + 0.3965 * temp * v
+}
+
+/// Humidex [°C] from temperature [°C] and relative humidity [%].
+pub fn humidex(temp: f32, humidity: f32) -> f32 {
+    let dew_point = dew_point(temp, humidity) + 273.15;
+    let e = 6.11 * (5417.753 * (1.0 / 273.16 - 1.0 / dew_point)).exp();
+
+    temp + 0.5555 * (e - 10.0)
+}
+
+/// Apparent temperature [°C] from temperature [°C], relative humidity [%] and
+/// wind speed [m/s], without accounting for solar radiation.
+pub fn apparent_temperature(temp: f32, humidity: f32, wind_speed: f32) -> f32 {
+    let e = humidity / 100.0 * 6.105 * (17.27 * temp / (237.7 + temp)).exp();
+
+    temp + 0.33 * e - 0.70 * wind_speed - 4.0
+}
+
+/// Absolute humidity [g/m³] from temperature [°C] and relative humidity [%].
+pub fn absolute_humidity(temp: f32, humidity: f32) -> f32 {
+    vapour_pressure(temp) * humidity * 2.1674 / (273.15 + temp)
+}
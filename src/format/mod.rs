@@ -0,0 +1,231 @@
+//! Rendering of weather results as text, for people or other tools.
+//!
+//! Single observations and aggregates can be rendered as:
+//!
+//! * a human-readable table, one city per line
+//! * pretty-printed JSON, as sent by the server
+//! * CSV, with the fixed columns listed in [CSV_COLUMNS](constant.CSV_COLUMNS.html),
+//!   optionally without the header line
+//! * newline-delimited JSON, one city per line
+
+use std::io::{self, Write};
+use std::str::FromStr;
+use serde::Serialize;
+use data::{self, CompassPoints, Observations, WeatherAggregate, WeatherBoxAggregate, WeatherInfo};
+
+/// Columns of the CSV output, as dotted paths into
+/// [WeatherInfo](../data/struct.WeatherInfo.html). They don't depend on the
+/// fields present in the response, so that files can be appended to with
+/// [Format::CsvRows](enum.Format.html#variant.CsvRows).
+pub const CSV_COLUMNS: [&str; 30] = ["id",
+                                     "name",
+                                     "sys.country",
+                                     "coord.lat",
+                                     "coord.lon",
+                                     "dt",
+                                     "timezone",
+                                     "weather.0.id",
+                                     "weather.0.main",
+                                     "weather.0.description",
+                                     "weather.0.icon",
+                                     "main.temp",
+                                     "main.feels_like",
+                                     "main.temp_min",
+                                     "main.temp_max",
+                                     "main.pressure",
+                                     "main.humidity",
+                                     "main.sea_level",
+                                     "main.grnd_level",
+                                     "visibility",
+                                     "wind.speed",
+                                     "wind.deg",
+                                     "wind.gust",
+                                     "clouds.all",
+                                     "rain.1h",
+                                     "rain.3h",
+                                     "snow.1h",
+                                     "snow.3h",
+                                     "sys.sunrise",
+                                     "sys.sunset"];
+
+/// Output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+    /// CSV without the header line
+    CsvRows,
+    Ndjson,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    /// Parses the lowercase name of a format, e.g. "csv".
+    fn from_str(name: &str) -> ::std::result::Result<Format, String> {
+        match name {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "csv-rows" => Ok(Format::CsvRows),
+            "ndjson" => Ok(Format::Ndjson),
+            _ => Err(format!("unknown output format: {}", name)),
+        }
+    }
+}
+
+/// Implemented by the responses that can be rendered.
+pub trait Render: Serialize {
+    /// Observations making up the lines of the table, CSV and NDJSON outputs.
+    fn rows(&self) -> &[WeatherInfo];
+
+    /// Writes the response in the given format.
+    fn write_to<W: Write>(&self, format: Format, out: &mut W) -> io::Result<()> {
+        match format {
+            Format::Table => write_table(self.rows(), out),
+            Format::Json => {
+                json::to_writer_pretty(&mut *out, &data::to_json(self))?;
+                writeln!(out)
+            }
+            Format::Csv => write_csv(self.rows(), true, out),
+            Format::CsvRows => write_csv(self.rows(), false, out),
+            Format::Ndjson => {
+                for row in self.rows() {
+                    json::to_writer(&mut *out, &data::to_json(row))?;
+                    writeln!(out)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Renders the response in the given format.
+    fn render(&self, format: Format) -> String {
+        let mut out = Vec::new();
+        self.write_to(format, &mut out).expect("writing to memory doesn't fail");
+
+        String::from_utf8(out).expect("rendered text is valid UTF-8")
+    }
+}
+
+impl Render for WeatherInfo {
+    fn rows(&self) -> &[WeatherInfo] {
+        ::std::slice::from_ref(self)
+    }
+}
+
+impl Render for WeatherAggregate {
+    fn rows(&self) -> &[WeatherInfo] {
+        self.observations()
+    }
+}
+
+impl Render for WeatherBoxAggregate {
+    fn rows(&self) -> &[WeatherInfo] {
+        self.observations()
+    }
+}
+
+/// Header and value of a table column, and whether it is right-aligned.
+type Column = (&'static str, fn(&WeatherInfo) -> Option<String>, bool);
+
+const TABLE_COLUMNS: [Column; 9] = [("City", |i| i.name.clone(), false),
+                                    ("Country", |i| i.sys.as_ref()?.country.clone(), false),
+                                    ("Temp", |i| decimal(i.main.as_ref()?.temp?, 1), true),
+                                    ("Feels", |i| decimal(i.main.as_ref()?.feels_like?, 1), true),
+                                    ("Humidity", |i| percent(i.main.as_ref()?.humidity?), true),
+                                    ("Pressure", |i| decimal(i.main.as_ref()?.pressure?, 0), true),
+                                    ("Wind", wind, true),
+                                    ("Clouds", |i| percent(i.clouds.as_ref()?.all?), true),
+                                    ("Conditions", conditions, false)];
+
+fn write_table<W: Write>(rows: &[WeatherInfo], out: &mut W) -> io::Result<()> {
+    let cells: Vec<Vec<String>> = rows.iter()
+        .map(|row| {
+            TABLE_COLUMNS.iter()
+                .map(|&(_, value, _)| value(row).unwrap_or_else(|| "-".to_string()))
+                .collect()
+        })
+        .collect();
+
+    let widths: Vec<usize> = TABLE_COLUMNS.iter()
+        .enumerate()
+        .map(|(i, &(header, _, _))| {
+            cells.iter().map(|row| row[i].chars().count()).fold(header.len(), usize::max)
+        })
+        .collect();
+
+    let headers: Vec<String> = TABLE_COLUMNS.iter().map(|col| col.0.to_string()).collect();
+    let rules: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+
+    for line in Some(headers).into_iter().chain(Some(rules)).chain(cells) {
+        let padded: Vec<String> = line.iter()
+            .zip(&widths)
+            .zip(TABLE_COLUMNS.iter())
+            .map(|((cell, &width), &(_, _, right))| {
+                let padding = " ".repeat(width - cell.chars().count());
+                if right { padding + cell } else { cell.clone() + &padding }
+            })
+            .collect();
+
+        writeln!(out, "{}", padded.join("  ").trim_end())?;
+    }
+
+    Ok(())
+}
+
+fn write_csv<W: Write>(rows: &[WeatherInfo], header: bool, out: &mut W) -> io::Result<()> {
+    if header {
+        writeln!(out, "{}", CSV_COLUMNS.join(","))?;
+    }
+
+    for row in rows {
+        let value = data::to_json(row);
+        let fields: Vec<String> = CSV_COLUMNS.iter()
+            .map(|column| {
+                match value.pointer(&format!("/{}", column.replace('.', "/"))) {
+                    Some(json::Value::String(text)) => csv_escape(text),
+                    Some(json::Value::Null) | None => String::new(),
+                    Some(other) => other.to_string(),
+                }
+            })
+            .collect();
+
+        writeln!(out, "{}", fields.join(","))?;
+    }
+
+    Ok(())
+}
+
+/// Quotes a CSV field if needed.
+fn csv_escape(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn decimal(value: f32, precision: usize) -> Option<String> {
+    Some(format!("{:.*}", precision, value))
+}
+
+fn percent(value: i32) -> Option<String> {
+    Some(format!("{}%", value))
+}
+
+fn wind(info: &WeatherInfo) -> Option<String> {
+    let wind = info.wind.as_ref()?;
+    let speed = format!("{:.1}", wind.speed?);
+
+    Some(match wind.compass(CompassPoints::Sixteen) {
+        Some(compass) => format!("{} {}", speed, compass),
+        None => speed,
+    })
+}
+
+fn conditions(info: &WeatherInfo) -> Option<String> {
+    let weather = info.weather.as_ref()?.first()?;
+    weather.description.clone().or_else(|| weather.main.clone())
+}
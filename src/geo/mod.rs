@@ -0,0 +1,283 @@
+//! Geographic types and helpers shared by the location-based facilities of the crate.
+
+use std::convert::TryFrom;
+use {Error, Result};
+
+/// Mean radius of the Earth [km]
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Maximum number of tiles a box is split into.
+pub const MAX_TILES: usize = 1 << 16;
+
+/// Great-circle distance [km] between two points given as latitude and
+/// longitude in degrees, computed with the haversine formula.
+pub fn distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let dphi = (lat2 - lat1).to_radians();
+    let dlambda = (lon2 - lon1).to_radians();
+
+    let a = (dphi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (dlambda / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+/// Geographic coordinates, with latitude within [-90, 90] and longitude
+/// normalized within [-180, 180). Deserialization validates them like
+/// [new](#method.new).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawLatLon")]
+pub struct LatLon {
+    lat: f64,
+    lon: f64,
+}
+
+/// Coordinates as deserialized, before validation.
+#[derive(Deserialize)]
+struct RawLatLon {
+    lat: f64,
+    lon: f64,
+}
+
+impl TryFrom<RawLatLon> for LatLon {
+    type Error = String;
+
+    fn try_from(raw: RawLatLon) -> ::std::result::Result<LatLon, String> {
+        LatLon::new(raw.lat, raw.lon).map_err(|err| match err {
+            Error::InvalidCoordinates(msg) => msg,
+            err => format!("{:?}", err),
+        })
+    }
+}
+
+impl LatLon {
+    /// Validates the given latitude and longitude, in degrees. Longitudes
+    /// outside [-180, 180) are wrapped around.
+    pub fn new(lat: f64, lon: f64) -> Result<LatLon> {
+        if !(-90.0..=90.0).contains(&lat) {
+            return Err(Error::InvalidCoordinates(format!("latitude {} out of range", lat)));
+        }
+        if !lon.is_finite() {
+            return Err(Error::InvalidCoordinates(format!("longitude {} out of range", lon)));
+        }
+
+        Ok(LatLon {
+            lat,
+            lon: normalize_lon(lon),
+        })
+    }
+
+    /// Latitude [deg]
+    pub fn lat(&self) -> f64 {
+        self.lat
+    }
+
+    /// Longitude [deg]
+    pub fn lon(&self) -> f64 {
+        self.lon
+    }
+
+    /// Great-circle distance [km] to another point.
+    pub fn distance(&self, other: &LatLon) -> f64 {
+        distance(self.lat, self.lon, other.lat, other.lon)
+    }
+}
+
+/// Rectangle specified by geographic coordinates (latitude and longitude).
+///
+/// A box whose `left` edge lies east of its `right` edge crosses the 180th
+/// meridian. Use [new](#method.new) to validate and normalize the edges.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub top: f64,
+    pub bottom: f64,
+    pub left: f64,
+    pub right: f64,
+}
+
+impl BoundingBox {
+    /// Validates the edges of a box, in degrees. Longitudes are wrapped
+    /// around, and boxes spanning 360° or more cover all longitudes.
+    pub fn new(top: f64, bottom: f64, left: f64, right: f64) -> Result<BoundingBox> {
+        let (nw, se) = (LatLon::new(top, left)?, LatLon::new(bottom, right)?);
+
+        if bottom > top {
+            return Err(Error::InvalidCoordinates(format!("bottom {} above top {}", bottom, top)));
+        }
+
+        let (left, right) = if right - left >= 360.0 {
+            (-180.0, 180.0)
+        } else if se.lon == -180.0 && right > left {
+            (nw.lon, 180.0)
+        } else {
+            (nw.lon, se.lon)
+        };
+
+        Ok(BoundingBox {
+            top,
+            bottom,
+            left,
+            right,
+        })
+    }
+
+    /// Smallest box containing all points within `radius` [km] of `center`.
+    pub fn around(center: &LatLon, radius: f64) -> BoundingBox {
+        BoundingBox {
+            top: center.lat,
+            bottom: center.lat,
+            left: center.lon,
+            right: center.lon,
+        }
+        .expand(radius)
+    }
+
+    /// Whether the box crosses the 180th meridian.
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.left > self.right
+    }
+
+    /// Width of the box [deg]
+    pub fn width(&self) -> f64 {
+        if self.crosses_antimeridian() {
+            self.right - self.left + 360.0
+        } else {
+            self.right - self.left
+        }
+    }
+
+    /// Height of the box [deg]
+    pub fn height(&self) -> f64 {
+        self.top - self.bottom
+    }
+
+    /// Splits a box crossing the 180th meridian into its eastern and western
+    /// parts. Other boxes are returned as-is.
+    pub fn split(&self) -> Vec<BoundingBox> {
+        if !self.crosses_antimeridian() {
+            return vec![*self];
+        }
+
+        vec![BoundingBox { right: 180.0, ..*self }, BoundingBox { left: -180.0, ..*self }]
+    }
+
+    /// Splits the box into a grid of equally-sized tiles, none of them wider
+    /// or taller than `max_size` [deg]. Tiles never cross the 180th meridian.
+    /// Fails if `max_size` is not a positive number or yields more than
+    /// [MAX_TILES](constant.MAX_TILES.html).
+    pub fn tiles(&self, max_size: f64) -> Result<Vec<BoundingBox>> {
+        if max_size.is_nan() || max_size <= 0.0 {
+            return Err(Error::InvalidCoordinates(format!("invalid tile size {}", max_size)));
+        }
+        let parts: Vec<(BoundingBox, f64, f64)> = self.split()
+            .into_iter()
+            .map(|part| {
+                let rows = (part.height() / max_size).ceil().max(1.0);
+                let cols = (part.width() / max_size).ceil().max(1.0);
+                (part, rows, cols)
+            })
+            .collect();
+        if parts.iter().map(|&(_, rows, cols)| rows * cols).sum::<f64>() > MAX_TILES as f64 {
+            return Err(Error::InvalidCoordinates(format!("tile size {} too small for the box",
+                                                         max_size)));
+        }
+
+        let mut tiles = Vec::new();
+        for (part, rows, cols) in parts {
+            let (rows, cols) = (rows as usize, cols as usize);
+            let (width, height) = (part.width() / cols as f64, part.height() / rows as f64);
+
+            for row in 0..rows {
+                for col in 0..cols {
+                    tiles.push(BoundingBox {
+                        top: if row == 0 { part.top } else { part.top - row as f64 * height },
+                        bottom: if row + 1 == rows {
+                            part.bottom
+                        } else {
+                            part.top - (row + 1) as f64 * height
+                        },
+                        left: part.left + col as f64 * width,
+                        right: if col + 1 == cols {
+                            part.right
+                        } else {
+                            part.left + (col + 1) as f64 * width
+                        },
+                    });
+                }
+            }
+        }
+
+        Ok(tiles)
+    }
+
+    /// Center of the box.
+    pub fn center(&self) -> LatLon {
+        LatLon {
+            lat: (self.top + self.bottom) / 2.0,
+            lon: normalize_lon(self.left + self.width() / 2.0),
+        }
+    }
+
+    /// Whether the point lies within the box, edges included.
+    pub fn contains(&self, point: &LatLon) -> bool {
+        let in_lat = point.lat >= self.bottom && point.lat <= self.top;
+        let in_lon = if self.crosses_antimeridian() {
+            point.lon >= self.left || point.lon <= self.right
+        } else {
+            point.lon >= self.left && point.lon <= self.right
+        };
+
+        in_lat && in_lon
+    }
+
+    /// Surface [km²] covered by the box.
+    pub fn area(&self) -> f64 {
+        let band = self.top.to_radians().sin() - self.bottom.to_radians().sin();
+
+        EARTH_RADIUS_KM * EARTH_RADIUS_KM * band * self.width().to_radians()
+    }
+
+    /// Grows the box so that it contains all points within `distance` [km] of
+    /// the original one. Latitudes are capped at the poles, and boxes reaching
+    /// a pole cover all longitudes.
+    pub fn expand(&self, distance: f64) -> BoundingBox {
+        let dlat = (distance / EARTH_RADIUS_KM).to_degrees();
+        let top = (self.top + dlat).min(90.0);
+        let bottom = (self.bottom - dlat).max(-90.0);
+
+        let widest = top.abs().max(bottom.abs());
+        if widest >= 90.0 {
+            return BoundingBox {
+                top,
+                bottom,
+                left: -180.0,
+                right: 180.0,
+            };
+        }
+
+        let dlon = dlat / widest.to_radians().cos();
+        if self.width() + 2.0 * dlon >= 360.0 {
+            return BoundingBox {
+                top,
+                bottom,
+                left: -180.0,
+                right: 180.0,
+            };
+        }
+
+        BoundingBox {
+            top,
+            bottom,
+            left: normalize_lon(self.left - dlon),
+            right: normalize_lon(self.right + dlon),
+        }
+    }
+}
+
+/// Wraps a longitude within [-180, 180).
+fn normalize_lon(lon: f64) -> f64 {
+    if (-180.0..180.0).contains(&lon) {
+        lon
+    } else {
+        (lon + 180.0).rem_euclid(360.0) - 180.0
+    }
+}
@@ -0,0 +1,233 @@
+//! Conversion of weather results from and to GeoJSON.
+//!
+//! Every observation becomes a point feature located at its `coord`. The
+//! other fields are flattened into the properties of the feature, with
+//! dotted keys for nested fields (e.g. `main.temp`, `weather.0.description`)
+//! so that GIS tools can style and filter on them.
+
+use serde::de::Error as DecodeError;
+use data::{self, Observations, WeatherAggregate, WeatherBoxAggregate, WeatherInfo};
+use {Error, Result};
+
+/// Types that can be exported as a GeoJSON FeatureCollection.
+pub trait ToGeoJson {
+    fn to_geojson(&self) -> json::Value;
+}
+
+/// Types that can be imported from a GeoJSON FeatureCollection, as produced
+/// by [ToGeoJson](trait.ToGeoJson.html).
+pub trait FromGeoJson: Sized {
+    fn from_geojson(body: &str) -> Result<Self>;
+}
+
+impl ToGeoJson for WeatherAggregate {
+    fn to_geojson(&self) -> json::Value {
+        feature_collection(self)
+    }
+}
+
+impl ToGeoJson for WeatherBoxAggregate {
+    fn to_geojson(&self) -> json::Value {
+        feature_collection(self)
+    }
+}
+
+impl ToGeoJson for WeatherInfo {
+    fn to_geojson(&self) -> json::Value {
+        feature_collection(::std::slice::from_ref(self))
+    }
+}
+
+impl FromGeoJson for WeatherAggregate {
+    fn from_geojson(body: &str) -> Result<WeatherAggregate> {
+        let list = features(body)?;
+
+        Ok(WeatherAggregate {
+            count: Some(list.len() as i32),
+            list: Some(list),
+            ..WeatherAggregate::default()
+        })
+    }
+}
+
+impl FromGeoJson for WeatherBoxAggregate {
+    fn from_geojson(body: &str) -> Result<WeatherBoxAggregate> {
+        let list = features(body)?;
+
+        Ok(WeatherBoxAggregate {
+            cnt: Some(list.len() as i32),
+            list: Some(list),
+            ..WeatherBoxAggregate::default()
+        })
+    }
+}
+
+impl FromGeoJson for WeatherInfo {
+    /// Imports the first feature of the collection.
+    fn from_geojson(body: &str) -> Result<WeatherInfo> {
+        features(body)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| invalid(body, "empty FeatureCollection"))
+    }
+}
+
+/// Builds a FeatureCollection with one feature per observation.
+pub fn feature_collection<O>(observations: &O) -> json::Value
+    where O: Observations + ?Sized
+{
+    let features: Vec<json::Value> = observations.observations().iter().map(feature).collect();
+
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+/// Builds the point feature of a single observation. Observations without
+/// coordinates get a null geometry.
+pub fn feature(info: &WeatherInfo) -> json::Value {
+    let mut value = data::to_json(info);
+
+    let coord = value.as_object_mut().and_then(|fields| fields.remove("coord"));
+    let geometry = match coord {
+        Some(ref coord) if coord["lon"].is_number() && coord["lat"].is_number() => {
+            json!({
+                "type": "Point",
+                "coordinates": [coord["lon"], coord["lat"]],
+            })
+        }
+        _ => json::Value::Null,
+    };
+
+    let mut properties = json::Map::new();
+    flatten(String::new(), value, &mut properties);
+
+    let mut feature = json!({
+        "type": "Feature",
+        "geometry": geometry,
+        "properties": properties,
+    });
+    if let Some(id) = info.id {
+        feature["id"] = json::Value::from(id);
+    }
+
+    feature
+}
+
+/// Decodes the observations of a FeatureCollection.
+fn features(body: &str) -> Result<Vec<WeatherInfo>> {
+    let collection: json::Value = json::from_str(body)
+        .map_err(|err| Error::JsonDecodeError(body.to_string(), err))?;
+
+    if collection["type"] != "FeatureCollection" {
+        return Err(invalid(body, "not a FeatureCollection"));
+    }
+    let features = collection["features"]
+        .as_array()
+        .ok_or_else(|| invalid(body, "missing features"))?;
+
+    features.iter()
+        .map(|feature| {
+            let mut value = match feature["properties"].as_object() {
+                Some(properties) => unflatten(properties).map_err(|reason| invalid(body, &reason))?,
+                None => json::Value::Object(json::Map::new()),
+            };
+
+            // Features written by other tools may only carry the ID at their top level
+            if value["id"].is_null() && feature["id"].is_i64() {
+                value["id"] = feature["id"].clone();
+            }
+            if feature["geometry"]["type"] == "Point" {
+                let coordinates = &feature["geometry"]["coordinates"];
+                value["coord"] = json!({"lon": coordinates[0], "lat": coordinates[1]});
+            }
+
+            json::from_value(value).map_err(|err| Error::JsonDecodeError(body.to_string(), err))
+        })
+        .collect()
+}
+
+/// Moves the leaves of `value` into `properties`, keyed by their dotted path.
+/// Null values are skipped, empty arrays and objects are kept as leaves.
+fn flatten(path: String, value: json::Value, properties: &mut json::Map<String, json::Value>) {
+    let child = |key: &str| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+
+    match value {
+        json::Value::Null => (),
+        json::Value::Object(ref fields) if fields.is_empty() && !path.is_empty() => {
+            properties.insert(path, value);
+        }
+        json::Value::Array(ref items) if items.is_empty() => {
+            properties.insert(path, value);
+        }
+        json::Value::Object(fields) => {
+            for (key, value) in fields {
+                flatten(child(&key), value, properties);
+            }
+        }
+        json::Value::Array(items) => {
+            for (i, value) in items.into_iter().enumerate() {
+                flatten(child(&i.to_string()), value, properties);
+            }
+        }
+        value => {
+            properties.insert(path, value);
+        }
+    }
+}
+
+/// Rebuilds the nested value whose leaves were flattened into `properties`.
+/// Numeric path segments denote array items, which must not leave gaps.
+fn unflatten(properties: &json::Map<String, json::Value>) -> ::std::result::Result<json::Value, String> {
+    let mut root = json::Value::Object(json::Map::new());
+
+    // Visit array items by increasing index, e.g. "list.2" before "list.10"
+    let mut keys: Vec<(Vec<&str>, &json::Value)> =
+        properties.iter().map(|(key, leaf)| (key.split('.').collect(), leaf)).collect();
+    keys.sort_by(|a, b| {
+        let order = |segment: &&str| (segment.parse::<usize>().ok(), segment.to_string());
+        a.0.iter().map(order).cmp(b.0.iter().map(order))
+    });
+
+    for (segments, leaf) in keys {
+        let mut node = &mut root;
+
+        for (i, segment) in segments.iter().enumerate() {
+            let child = match segments.get(i + 1) {
+                None => leaf.clone(),
+                Some(next) if next.parse::<usize>().is_ok() => json::Value::Array(Vec::new()),
+                Some(_) => json::Value::Object(json::Map::new()),
+            };
+
+            node = match *node {
+                json::Value::Object(ref mut fields) => {
+                    fields.entry(segment.to_string()).or_insert(child)
+                }
+                json::Value::Array(ref mut items) => {
+                    let index = segment.parse::<usize>().unwrap_or(items.len());
+                    if index > items.len() {
+                        return Err(format!("array index {} out of order in property {}",
+                                           segment,
+                                           segments.join(".")));
+                    }
+                    if index == items.len() {
+                        items.push(json::Value::Null);
+                    }
+                    if items[index].is_null() {
+                        items[index] = child;
+                    }
+                    &mut items[index]
+                }
+                // Conflicting keys, e.g. both "main" and "main.temp"
+                _ => break,
+            };
+        }
+    }
+
+    Ok(root)
+}
+
+fn invalid(body: &str, reason: &str) -> Error {
+    Error::JsonDecodeError(body.to_string(), json::Error::custom(reason))
+}
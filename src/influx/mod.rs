@@ -0,0 +1,214 @@
+//! Observations in the InfluxDB line protocol, for time-series databases.
+//!
+//! Every observation becomes one line, tagged by the ID, name and country of
+//! the city, with the numeric values as fields and `dt` as timestamp:
+//!
+//! ```text
+//! weather,city_id=2643743,city=London,country=GB temp=282.55,humidity=81i,... 1485789600000000000
+//! ```
+//!
+//! Integer values, such as humidity, get the `i` suffix of integer fields.
+//! Missing values and tags are left out, as are observations without any of
+//! the selected fields.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use json;
+use data;
+use format::Render;
+
+/// Keys of the available fields, with their dotted path into
+/// [WeatherInfo](../data/struct.WeatherInfo.html).
+pub const FIELDS: [(&str, &str); 23] = [("lat", "coord.lat"),
+                                        ("lon", "coord.lon"),
+                                        ("weather_id", "weather.0.id"),
+                                        ("temp", "main.temp"),
+                                        ("feels_like", "main.feels_like"),
+                                        ("temp_min", "main.temp_min"),
+                                        ("temp_max", "main.temp_max"),
+                                        ("pressure", "main.pressure"),
+                                        ("humidity", "main.humidity"),
+                                        ("sea_level", "main.sea_level"),
+                                        ("grnd_level", "main.grnd_level"),
+                                        ("visibility", "visibility"),
+                                        ("wind_speed", "wind.speed"),
+                                        ("wind_deg", "wind.deg"),
+                                        ("wind_gust", "wind.gust"),
+                                        ("clouds", "clouds.all"),
+                                        ("rain_1h", "rain.1h"),
+                                        ("rain_3h", "rain.3h"),
+                                        ("snow_1h", "snow.1h"),
+                                        ("snow_3h", "snow.3h"),
+                                        ("sunrise", "sys.sunrise"),
+                                        ("sunset", "sys.sunset"),
+                                        ("timezone", "timezone")];
+
+/// Unit of the timestamps, which must match the one given to the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl Precision {
+    /// Number of units in a second.
+    fn per_second(&self) -> i64 {
+        match *self {
+            Precision::Seconds => 1,
+            Precision::Milliseconds => 1_000,
+            Precision::Microseconds => 1_000_000,
+            Precision::Nanoseconds => 1_000_000_000,
+        }
+    }
+}
+
+impl FromStr for Precision {
+    type Err = String;
+
+    /// Parses the name used by InfluxDB, e.g. "ms".
+    fn from_str(name: &str) -> ::std::result::Result<Precision, String> {
+        match name {
+            "s" => Ok(Precision::Seconds),
+            "ms" => Ok(Precision::Milliseconds),
+            "us" => Ok(Precision::Microseconds),
+            "ns" => Ok(Precision::Nanoseconds),
+            _ => Err(format!("unknown precision: {}", name)),
+        }
+    }
+}
+
+impl fmt::Display for Precision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Precision::Seconds => "s",
+            Precision::Milliseconds => "ms",
+            Precision::Microseconds => "us",
+            Precision::Nanoseconds => "ns",
+        })
+    }
+}
+
+/// Serializer of observations into line protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineProtocol {
+    measurement: String,
+    fields: Vec<(&'static str, &'static str)>,
+    precision: Precision,
+}
+
+impl LineProtocol {
+    /// Creates a serializer of all the fields into the `weather` measurement,
+    /// with timestamps in nanoseconds.
+    pub fn new() -> LineProtocol {
+        LineProtocol {
+            measurement: "weather".to_string(),
+            fields: FIELDS.to_vec(),
+            precision: Precision::Nanoseconds,
+        }
+    }
+
+    /// Change the name of the measurement.
+    pub fn measurement(mut self, name: &str) -> Self {
+        self.measurement = name.to_string();
+        self
+    }
+
+    /// Only write the given fields, in the given order, by their key in
+    /// [FIELDS](constant.FIELDS.html). Fails on unknown keys.
+    pub fn fields<'k, I>(mut self, keys: I) -> ::std::result::Result<Self, String>
+        where I: IntoIterator<Item = &'k str>
+    {
+        self.fields = keys.into_iter()
+            .map(|key| {
+                FIELDS.iter()
+                    .find(|&&(name, _)| name == key)
+                    .cloned()
+                    .ok_or_else(|| format!("unknown field: {}", key))
+            })
+            .collect::<::std::result::Result<_, _>>()?;
+        Ok(self)
+    }
+
+    /// Change the unit of the timestamps. Default is nanoseconds.
+    pub fn precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Writes one line per observation of the response.
+    pub fn write_to<R, W>(&self, value: &R, out: &mut W) -> io::Result<()>
+        where R: Render + ?Sized,
+              W: Write
+    {
+        for row in value.rows() {
+            let json = data::to_json(row);
+            let fields: Vec<String> = self.fields
+                .iter()
+                .filter_map(|&(key, path)| {
+                    let value = json.pointer(&format!("/{}", path.replace('.', "/")))?;
+                    Some(format!("{}={}", key, field_value(value)?))
+                })
+                .collect();
+            if fields.is_empty() {
+                continue;
+            }
+
+            let mut line = escape(&self.measurement, &[',', ' ']);
+            let tags = [("city_id", row.id.map(|id| id.to_string())),
+                        ("city", row.name.clone()),
+                        ("country", row.sys.as_ref().and_then(|sys| sys.country.clone()))];
+            for &(key, ref value) in &tags {
+                if let Some(value) = value.as_ref().filter(|value| !value.is_empty()) {
+                    line += &format!(",{}={}", key, escape(value, &[',', '=', ' ']));
+                }
+            }
+            line += " ";
+            line += &fields.join(",");
+            if let Some(dt) = row.dt {
+                line += &format!(" {}", dt * self.precision.per_second());
+            }
+
+            writeln!(out, "{}", line)?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders the observations of the response.
+    pub fn render<R: Render + ?Sized>(&self, value: &R) -> String {
+        let mut out = Vec::new();
+        self.write_to(value, &mut out).expect("writing to memory doesn't fail");
+
+        String::from_utf8(out).expect("rendered text is valid UTF-8")
+    }
+}
+
+impl Default for LineProtocol {
+    fn default() -> LineProtocol {
+        LineProtocol::new()
+    }
+}
+
+/// Field value of a number, with the suffix of integers.
+fn field_value(value: &json::Value) -> Option<String> {
+    match *value {
+        json::Value::Number(ref number) if number.is_f64() => Some(number.to_string()),
+        json::Value::Number(ref number) => Some(format!("{}i", number)),
+        _ => None,
+    }
+}
+
+/// Escapes the given characters of a measurement, tag key or tag value.
+fn escape(text: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -0,0 +1,197 @@
+//! Interpolation of scattered observations onto a regular grid.
+//!
+//! Box and circle queries return the weather of the cities in an area, which
+//! are irregularly spaced. An [Interpolator](struct.Interpolator.html)
+//! estimates one of their scalar fields at regularly spaced points, e.g. to
+//! draw a map layer.
+
+use data::Observations;
+use geo::{BoundingBox, LatLon};
+use {Error, Result};
+
+pub use data::Field;
+
+/// Distance [km] under which a grid point is considered to coincide with an observation.
+const SAME_POINT_KM: f64 = 1e-6;
+
+/// Maximum number of points of a grid.
+pub const MAX_GRID_POINTS: usize = 1 << 24;
+
+/// Interpolation method.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    /// Value of the closest observation
+    Nearest,
+    /// Average of the observations, weighted by the inverse of their distance
+    /// raised to the given power (usually 2)
+    InverseDistance(f64),
+}
+
+/// Values of a field at regularly spaced points.
+///
+/// Points are `resolution` degrees apart, starting from the north-west corner
+/// of the box. The south and east edges are only included when the size of
+/// the box is a multiple of the resolution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+    /// Area covered by the grid
+    pub bbox: BoundingBox,
+    /// Spacing between points [deg]
+    pub resolution: f64,
+    /// Number of points from north to south
+    pub rows: usize,
+    /// Number of points from west to east
+    pub cols: usize,
+    /// Values row by row, from north to south and west to east. NaN where
+    /// no observation is close enough.
+    pub values: Vec<f64>,
+}
+
+impl Grid {
+    /// Value at the given point of the grid.
+    pub fn get(&self, row: usize, col: usize) -> Option<f64> {
+        if row < self.rows && col < self.cols {
+            Some(self.values[row * self.cols + col])
+        } else {
+            None
+        }
+    }
+
+    /// Coordinates of the given point of the grid.
+    pub fn point(&self, row: usize, col: usize) -> Option<LatLon> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+        let lat = self.bbox.top - row as f64 * self.resolution;
+        let lon = self.bbox.left + col as f64 * self.resolution;
+
+        LatLon::new(lat.max(-90.0), lon).ok()
+    }
+}
+
+/// Estimates a field of the observations at arbitrary points.
+#[derive(Debug, Clone)]
+pub struct Interpolator {
+    field: Field,
+    method: Method,
+    max_distance: Option<f64>,
+}
+
+impl Interpolator {
+    /// Creates an interpolator for the given field, using inverse distance
+    /// weighting with a power of 2 and no maximum distance.
+    pub fn new(field: Field) -> Interpolator {
+        Interpolator {
+            field,
+            method: Method::InverseDistance(2.0),
+            max_distance: None,
+        }
+    }
+
+    /// Change the interpolation method.
+    pub fn method(mut self, method: Method) -> Interpolator {
+        self.method = method;
+        self
+    }
+
+    /// Ignore observations farther than `distance` [km] from the estimated
+    /// point. Points without any observation within that distance are NaN.
+    pub fn max_distance(mut self, distance: f64) -> Interpolator {
+        self.max_distance = Some(distance);
+        self
+    }
+
+    /// Estimates the field at a single point, or NaN if no observation is
+    /// close enough.
+    pub fn at<O>(&self, observations: &O, point: &LatLon) -> f64
+        where O: Observations + ?Sized
+    {
+        self.estimate(&self.samples(observations), point)
+    }
+
+    /// Estimates the field on a grid covering the box, with points
+    /// `resolution` degrees apart. Fails if the box is invalid, or if the
+    /// resolution is not a positive number or yields more than
+    /// [MAX_GRID_POINTS](constant.MAX_GRID_POINTS.html).
+    pub fn grid<O>(&self, observations: &O, bbox: &BoundingBox, resolution: f64) -> Result<Grid>
+        where O: Observations + ?Sized
+    {
+        let bbox = BoundingBox::new(bbox.top, bbox.bottom, bbox.left, bbox.right)?;
+        if !(resolution.is_finite() && resolution > 0.0) {
+            return Err(Error::InvalidCoordinates(format!("invalid grid resolution {}", resolution)));
+        }
+        let (rows, cols) = (steps(bbox.height(), resolution), steps(bbox.width(), resolution));
+        if rows * cols > MAX_GRID_POINTS as f64 {
+            return Err(Error::InvalidCoordinates(format!("grid resolution {} too fine for the box",
+                                                         resolution)));
+        }
+
+        let samples = self.samples(observations);
+        let mut grid = Grid {
+            bbox,
+            resolution,
+            rows: rows as usize,
+            cols: cols as usize,
+            values: Vec::new(),
+        };
+
+        grid.values = (0..grid.rows)
+            .flat_map(|row| (0..grid.cols).map(move |col| (row, col)))
+            .map(|(row, col)| match grid.point(row, col) {
+                Some(point) => self.estimate(&samples, &point),
+                None => f64::NAN,
+            })
+            .collect();
+
+        Ok(grid)
+    }
+
+    /// Locations and values of the observations providing the field.
+    fn samples<O>(&self, observations: &O) -> Vec<(LatLon, f64)>
+        where O: Observations + ?Sized
+    {
+        observations.observations()
+            .iter()
+            .filter_map(|info| {
+                let location = info.coord.as_ref()?.lat_lon()?;
+                let value = self.field.value(info).filter(|v| v.is_finite())?;
+
+                Some((location, value))
+            })
+            .collect()
+    }
+
+    fn estimate(&self, samples: &[(LatLon, f64)], point: &LatLon) -> f64 {
+        let max_distance = self.max_distance.unwrap_or(f64::INFINITY);
+        let near = samples.iter()
+            .map(|&(location, value)| (point.distance(&location), value))
+            .filter(|&(distance, _)| distance <= max_distance);
+
+        match self.method {
+            Method::Nearest => {
+                near.min_by(|a, b| a.0.total_cmp(&b.0))
+                    .map_or(f64::NAN, |(_, value)| value)
+            }
+            Method::InverseDistance(power) => {
+                let (mut sum, mut weights) = (0.0, 0.0);
+
+                for (distance, value) in near {
+                    if distance < SAME_POINT_KM {
+                        return value;
+                    }
+
+                    let weight = distance.powf(-power);
+                    sum += weight * value;
+                    weights += weight;
+                }
+
+                if weights > 0.0 { sum / weights } else { f64::NAN }
+            }
+        }
+    }
+}
+
+/// Number of points spaced by `resolution` that fit along `size`.
+fn steps(size: f64, resolution: f64) -> f64 {
+    (size / resolution + 1e-9).floor() + 1.0
+}
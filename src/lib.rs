@@ -11,10 +11,42 @@
 //! * [Querying the current weather](struct.CurrentWeatherQuery.html)
 //!  * By city name
 //!  * By city ID
-//!  * By ZIP code
+//!  * By ZIP or postal code
 //!  * By coordinates (point, bounding box and bounding circle)
+//!  * By any of the above, stored as a [Location](enum.Location.html)
+//! * [Batches](batch/struct.BatchQuery.html) of queries for many locations, run concurrently
 //! * Multi-language queries
+//! * [Offline lookup](city/index.html) of city IDs from OWM's bulk city list,
+//!   by name or by nearest coordinates
 //! * Standard, metric and imperial units
+//! * [Derived metrics](derived/index.html) such as dew point and wind chill
+//! * [Sorting, extremes and statistics](data/trait.Observations.html) over
+//!   aggregate results
+//! * [Interpolation](interpolate/index.html) of box and circle results onto a regular grid
+//! * [GeoJSON](geojson/index.html) export and import of results
+//! * [Rendering](format/index.html) of results as tables, JSON, CSV or NDJSON
+//! * [Templated one-line output](template/index.html) for status bars such as i3bar and waybar
+//! * [Watching](watch/index.html) locations for condition changes, threshold
+//!   crossings and precipitation
+//! * [Alert rules](rules/index.html) with a small expression language, e.g.
+//!   `temp < 0 and rain present`
+//! * [Configuration](config/index.html) from a TOML file and environment variables,
+//!   with response caching and named locations
+//! * [InfluxDB line protocol](influx/index.html) export of results
+//! * [Prometheus](prometheus/index.html) metrics of observations and
+//!   [hub statistics](struct.HubStats.html)
+//! * [Lenient decoding](data/enum.DecodeMode.html) of malformed responses
+//!
+//! Optional cargo features:
+//!
+//! * `chrono`: UTC and location-local timestamps on
+//!   [WeatherInfo](data/struct.WeatherInfo.html) and [Sys](data/struct.Sys.html)
+//! * `cli`: the `owm` command-line client, see `owm --help`
+//! * `exporter`: the `owm-exporter` Prometheus exporter, see `owm-exporter --help`
+//! * `sqlite`: a [history store](store/index.html) of observations, with
+//!   daily rollups
+//! * `mqtt`: an [MQTT publisher](mqtt/index.html) of observations, with Home
+//!   Assistant discovery
 //!
 //! # Example
 //!
@@ -33,7 +65,17 @@
 //!           Error::HttpError(_)
 //!         | Error::BadRequest(_)
 //!         | Error::JsonDecodeError(_, _)
-//!         | Error::Failure(_) => println!("{:?}", e),
+//!         | Error::Failure(_)
+//!         | Error::IoError(_)
+//!         | Error::LocationNotFound(_)
+//!         | Error::AmbiguousLocation(_)
+//!         | Error::InvalidCoordinates(_)
+//!         | Error::InvalidTemplate(_)
+//!         | Error::InvalidConfig(_)
+//!         | Error::InvalidRule(_)
+//!         | Error::StorageError(_)
+//!         | Error::MqttError(_)
+//!         | Error::InvalidObservation(_) => println!("{:?}", e),
 //!     },
 //!     Ok(res) => println!("{:?}", res),
 //! }
@@ -41,19 +83,57 @@
 //! ```
 
 mod uri;
+mod ratelimit;
+mod cache;
+mod stats;
 pub mod data;
 pub mod current;
+pub mod batch;
+pub mod derived;
+pub mod city;
+pub mod geo;
+pub mod location;
+pub mod interpolate;
+pub mod geojson;
+pub mod format;
+pub mod template;
+pub mod config;
+pub mod watch;
+pub mod rules;
+pub mod prometheus;
+pub mod influx;
+#[cfg(feature = "sqlite")]
+pub mod store;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
 
 #[macro_use]
 extern crate serde_derive;
 extern crate hyper;
 extern crate serde;
+#[macro_use]
 extern crate serde_json as json;
+extern crate serde_path_to_error;
 extern crate url;
+extern crate flate2;
+extern crate unicode_normalization;
+extern crate toml;
+
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "sqlite")]
+extern crate rusqlite;
+#[cfg(feature = "mqtt")]
+extern crate rumqttc;
 
 use std::io::Read;
 use data::*;
 use current::*;
+use batch::*;
+
+pub use geo::{BoundingBox, LatLon};
+pub use location::Location;
+pub use stats::HubStats;
 
 #[derive(Debug)]
 pub enum Error {
@@ -68,6 +148,114 @@ pub enum Error {
 
     /// Indicates an HTTP repsonse with a non-success status code.
     Failure(hyper::client::Response),
+
+    /// An error occurred while reading a local file.
+    IoError(std::io::Error),
+
+    /// No known city matches the given location.
+    LocationNotFound(String),
+
+    /// Several known cities match the given location. Candidates included.
+    AmbiguousLocation(Vec<city::Candidate>),
+
+    /// The given coordinates are out of range. Details included.
+    InvalidCoordinates(String),
+
+    /// The output template is malformed. Details included.
+    InvalidTemplate(String),
+
+    /// The configuration is malformed or incomplete. Details included.
+    InvalidConfig(String),
+
+    /// An alert rule is malformed. Details included.
+    InvalidRule(String),
+
+    /// The history database could not be read or written. Details included.
+    StorageError(String),
+
+    /// Messages could not be published to the MQTT broker. Details included.
+    MqttError(String),
+
+    /// An observation lacks data needed by the operation, e.g. a city ID.
+    InvalidObservation(String),
+}
+
+/// Broad category of an [Error](enum.Error.html), e.g. to pick an exit code
+/// or a metric label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The server, or the MQTT broker, could not be reached.
+    Network,
+    /// The API key is missing, invalid or not allowed to access the API.
+    Auth,
+    /// The requested location is unknown.
+    NotFound,
+    /// The server response could not be decoded.
+    Decode,
+    /// The query was rejected or could not be built.
+    InvalidRequest,
+    /// Too many requests were sent with the API key.
+    RateLimited,
+    /// The server failed to handle the query.
+    Server,
+    /// A local file or database could not be accessed.
+    Io,
+}
+
+impl ErrorKind {
+    /// All the kinds, e.g. to initialize counters.
+    pub const ALL: [ErrorKind; 8] = [ErrorKind::Network,
+                                     ErrorKind::Auth,
+                                     ErrorKind::NotFound,
+                                     ErrorKind::Decode,
+                                     ErrorKind::InvalidRequest,
+                                     ErrorKind::RateLimited,
+                                     ErrorKind::Server,
+                                     ErrorKind::Io];
+
+    /// Lowercase name of the kind, e.g. "not_found".
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ErrorKind::Network => "network",
+            ErrorKind::Auth => "auth",
+            ErrorKind::NotFound => "not_found",
+            ErrorKind::Decode => "decode",
+            ErrorKind::InvalidRequest => "invalid_request",
+            ErrorKind::RateLimited => "rate_limited",
+            ErrorKind::Server => "server",
+            ErrorKind::Io => "io",
+        }
+    }
+}
+
+impl Error {
+    /// Category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        let status = match *self {
+            Error::HttpError(_) |
+            Error::MqttError(_) => return ErrorKind::Network,
+            Error::JsonDecodeError(_, _) => return ErrorKind::Decode,
+            Error::IoError(_) |
+            Error::StorageError(_) => return ErrorKind::Io,
+            Error::LocationNotFound(_) => return ErrorKind::NotFound,
+            Error::AmbiguousLocation(_) |
+            Error::InvalidCoordinates(_) |
+            Error::InvalidTemplate(_) |
+            Error::InvalidConfig(_) |
+            Error::InvalidRule(_) |
+            Error::InvalidObservation(_) => return ErrorKind::InvalidRequest,
+            Error::BadRequest(ref resp) => resp.cod.unwrap_or(400) as u16,
+            Error::Failure(ref res) => res.status.to_u16(),
+        };
+
+        match status {
+            401 | 403 => ErrorKind::Auth,
+            404 => ErrorKind::NotFound,
+            429 => ErrorKind::RateLimited,
+            500..=599 => ErrorKind::Server,
+            _ => ErrorKind::InvalidRequest,
+        }
+    }
 }
 
 /// A universal result type used as return for all calls.
@@ -77,6 +265,13 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub struct WeatherHub {
     client: hyper::Client,
     key: String,
+    base_url: String,
+    decode_mode: DecodeMode,
+    rate_limit: Option<ratelimit::RateLimiter>,
+    cache: Option<cache::ResponseCache>,
+    stats: stats::Recorder,
+    units: Option<Units>,
+    lang: Option<String>,
 }
 
 impl<'a> WeatherHub {
@@ -86,23 +281,113 @@ impl<'a> WeatherHub {
         WeatherHub {
             client: client,
             key: key.to_string(),
+            base_url: uri::DEFAULT_BASE_URL.to_string(),
+            decode_mode: DecodeMode::default(),
+            rate_limit: None,
+            cache: None,
+            stats: stats::Recorder::default(),
+            units: None,
+            lang: None,
         }
     }
 
+    /// Change the base URL of the API, e.g. to go through a proxy.
+    /// Default is http://api.openweathermap.org/data.
+    pub fn base_url(mut self, url: &str) -> WeatherHub {
+        self.base_url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Limit the number of requests sent per minute. Queries exceeding the
+    /// limit block until they are allowed to go out. Default is no limit.
+    pub fn rate_limit(mut self, requests_per_minute: u32) -> WeatherHub {
+        self.rate_limit = Some(ratelimit::RateLimiter::new(requests_per_minute));
+        self
+    }
+
+    /// Keep successful responses for `ttl`, up to `capacity` of them, and
+    /// answer identical queries from memory meanwhile. Default is no cache.
+    pub fn cache(mut self, ttl: std::time::Duration, capacity: usize) -> WeatherHub {
+        self.cache = Some(cache::ResponseCache::new(ttl, capacity));
+        self
+    }
+
+    /// Change the default units of the queries, which can still override it.
+    pub fn units(mut self, units: Units) -> WeatherHub {
+        self.units = Some(units);
+        self
+    }
+
+    /// Change the default language of the queries, which can still override it.
+    pub fn lang(mut self, lang: &str) -> WeatherHub {
+        self.lang = Some(lang.to_string());
+        self
+    }
+
+    /// Change how server responses are decoded. Default is Strict.
+    ///
+    /// In [Lenient](data/enum.DecodeMode.html) mode, malformed fields are
+    /// returned as `None` and listed in the response's `warnings` instead of
+    /// failing the whole query.
+    pub fn decode_mode(mut self, mode: DecodeMode) -> WeatherHub {
+        self.decode_mode = mode;
+        self
+    }
+
     /// Provides access to the current-weather facilities.
     pub fn current(&'a self) -> CurrentWeatherQuery<'a> {
-        CurrentWeatherQuery::new(&self, {
-            let mut ub = uri::UriBuilder::new();
-            ub.param("appid", self.key.clone());
-            ub
-        })
+        CurrentWeatherQuery::new(&self, self.builder())
+    }
+
+    /// Provides access to batches of queries for many locations, run concurrently.
+    pub fn batch(&'a self) -> BatchQuery<'a> {
+        BatchQuery::new(self, self.builder())
+    }
+
+    /// URI builder with the key and default parameters of the hub.
+    fn builder(&'a self) -> uri::UriBuilder<'a> {
+        let mut ub = uri::UriBuilder::new(&self.base_url);
+        ub.param("appid", self.key.clone());
+        if let Some(units) = self.units {
+            ub.param("units", units.to_string());
+        }
+        if let Some(ref lang) = self.lang {
+            ub.param("lang", lang.clone());
+        }
+        ub
+    }
+
+    /// Counters of the queries run so far, e.g. for monitoring.
+    pub fn stats(&self) -> HubStats {
+        self.stats.snapshot()
     }
 
     /// Does the actual API call, parses the response and handles any errors.
     fn run_query<D>(&'a self, query: String) -> Result<(hyper::client::Response, D)>
-        where D: serde::Deserialize
+        where D: ApiResponse
+    {
+        let result = self.fetch(&query);
+        if let Err(ref err) = result {
+            self.stats.error(err.kind());
+        }
+        result
+    }
+
+    fn fetch<D>(&'a self, query: &str) -> Result<(hyper::client::Response, D)>
+        where D: ApiResponse
     {
-        let req_result = self.client.request(hyper::method::Method::Get, &query).send();
+        if let Some((res, body)) = self.cache.as_ref().and_then(|cache| cache.get(query)) {
+            self.stats.cache_hit();
+            return data::decode(&body, self.decode_mode).map(|decoded| (res, decoded));
+        }
+
+        if let Some(ref limiter) = self.rate_limit {
+            limiter.wait();
+        }
+
+        let start = std::time::Instant::now();
+        let req_result = self.client.request(hyper::method::Method::Get, query).send();
+        self.stats.request(start.elapsed());
 
         match req_result {
             Err(err) => return Err(Error::HttpError(err)),
@@ -117,27 +402,19 @@ impl<'a> WeatherHub {
                 }
                 let mut json_resp = String::new();
                 res.read_to_string(&mut json_resp).unwrap();
-                return match json::from_str(&json_resp) {
-                           Ok(decoded) => Ok((res, decoded)),
-                           Err(err) => Err(Error::JsonDecodeError(json_resp, err)),
-                       };
+                if let Some(ref cache) = self.cache {
+                    cache.put(query, &res, &json_resp);
+                }
+                return data::decode(&json_resp, self.decode_mode).map(|decoded| (res, decoded));
             }
         }
     }
 }
 
-/// Rectangle specified by geographic coordinates (latitude and longitude).
-#[derive(Debug)]
-pub struct BoundingBox {
-    pub top: f32,
-    pub bottom: f32,
-    pub left: f32,
-    pub right: f32,
-}
-
 /// Units format for this query.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Units {
+    Standard,
     Metric,
     Imperial,
 }
@@ -145,6 +422,7 @@ pub enum Units {
 impl ToString for Units {
     fn to_string(&self) -> String {
         match self {
+            &Units::Standard => "standard".to_string(),
             &Units::Metric => "metric".to_string(),
             &Units::Imperial => "imperial".to_string(),
         }
@@ -0,0 +1,66 @@
+//! Locations the weather can be queried for.
+
+use geo::LatLon;
+use Result;
+
+/// Any of the ways a location can be specified to the API.
+///
+/// Locations can be stored, e.g. in configuration files, and passed to
+/// [at](../current/struct.CurrentWeatherQuery.html#method.at). They are
+/// serialized as objects tagged by their `type`:
+///
+/// ```json
+/// {"type": "postal_code", "code": "SW1A 1AA", "country": "GB"}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Location {
+    /// City name, with optional state (US only, the country then defaults
+    /// to US) and country codes
+    Name {
+        city: String,
+        #[serde(default)]
+        state: Option<String>,
+        #[serde(default)]
+        country: Option<String>,
+    },
+    /// City ID, see [CityList](../city/struct.CityList.html)
+    Id { id: i32 },
+    /// Postal code, with optional country code (default is US)
+    PostalCode {
+        code: String,
+        #[serde(default)]
+        country: Option<String>,
+    },
+    /// Geographic coordinates
+    Coords(LatLon),
+}
+
+impl Location {
+    /// City name and optional country code.
+    pub fn name(city: &str, country: Option<&str>) -> Location {
+        Location::Name {
+            city: city.to_string(),
+            state: None,
+            country: country.map(str::to_string),
+        }
+    }
+
+    /// City ID.
+    pub fn id(id: i32) -> Location {
+        Location::Id { id }
+    }
+
+    /// Postal code and optional country code.
+    pub fn postal_code(code: &str, country: Option<&str>) -> Location {
+        Location::PostalCode {
+            code: code.to_string(),
+            country: country.map(str::to_string),
+        }
+    }
+
+    /// Geographic coordinates, which are validated.
+    pub fn coords(lat: f64, lon: f64) -> Result<Location> {
+        LatLon::new(lat, lon).map(Location::Coords)
+    }
+}
@@ -0,0 +1,375 @@
+//! Publishing of observations to an MQTT broker, with Home Assistant
+//! discovery.
+//!
+//! Requires the `mqtt` cargo feature. Every observation is published as
+//! JSON to a state topic of its city, `owm/{city_id}` by default. The first
+//! time a city is published, retained
+//! [discovery](https://www.home-assistant.io/integrations/mqtt/#mqtt-discovery)
+//! messages declare its temperature, humidity, pressure, wind and condition
+//! sensors, grouped in a device named after the city:
+//!
+//! ```no_run
+//! # extern crate hyper;
+//! # extern crate owm;
+//! # use owm::{Location, Units, WeatherHub};
+//! # use owm::mqtt::{Messages, MqttOptions, Publisher};
+//! # fn main() -> owm::Result<()> {
+//! let hub = WeatherHub::new(hyper::Client::new(), "YOUR_API_KEY").units(Units::Metric);
+//! let messages = Messages::new().state_topic("weather/{city}").units(Units::Metric);
+//! let mut publisher = Publisher::connect(MqttOptions::new("owm", "localhost", 1883), messages)?;
+//!
+//! let (_, info) = hub.current().at(&Location::id(2643743))?;
+//! publisher.publish(&info)?;
+//! publisher.disconnect()
+//! # }
+//! ```
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use rumqttc::{Client, Event, Incoming, Outgoing};
+use data::{self, WeatherInfo};
+use {Error, Result, Units};
+
+pub use rumqttc::{MqttOptions, QoS};
+
+/// Time waited before reconnecting to the broker.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Number of messages queued before publishing blocks.
+const QUEUE_CAPACITY: usize = 64;
+
+/// Time waited for the queued messages to be delivered when disconnecting.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Key, name, value template and device class of a sensor.
+type Sensor = (&'static str, &'static str, &'static str, Option<&'static str>);
+
+const SENSORS: [Sensor; 6] = [("temperature",
+                               "Temperature",
+                               "{{ value_json.main.temp }}",
+                               Some("temperature")),
+                              ("humidity",
+                               "Humidity",
+                               "{{ value_json.main.humidity }}",
+                               Some("humidity")),
+                              ("pressure",
+                               "Pressure",
+                               "{{ value_json.main.pressure }}",
+                               Some("atmospheric_pressure")),
+                              ("wind_speed",
+                               "Wind speed",
+                               "{{ value_json.wind.speed }}",
+                               Some("wind_speed")),
+                              ("wind_bearing", "Wind bearing", "{{ value_json.wind.deg }}", None),
+                              ("condition",
+                               "Condition",
+                               "{{ value_json.weather[0].description }}",
+                               None)];
+
+/// Topics and payloads of the messages about observations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Messages {
+    state_topic: String,
+    discovery_prefix: String,
+    units: Units,
+}
+
+impl Messages {
+    /// Creates messages published to `owm/{city_id}`, with discovery under
+    /// `homeassistant`, for observations in standard units.
+    pub fn new() -> Messages {
+        Messages {
+            state_topic: "owm/{city_id}".to_string(),
+            discovery_prefix: "homeassistant".to_string(),
+            units: Units::Standard,
+        }
+    }
+
+    /// Change the state topic. `{city_id}` is replaced by the ID of the city
+    /// and `{city}` by its name, in lowercase with underscores.
+    pub fn state_topic(mut self, pattern: &str) -> Self {
+        self.state_topic = pattern.to_string();
+        self
+    }
+
+    /// Change the discovery prefix configured in Home Assistant.
+    pub fn discovery_prefix(mut self, prefix: &str) -> Self {
+        self.discovery_prefix = prefix.to_string();
+        self
+    }
+
+    /// Units of the observations, which set the units of the sensors.
+    pub fn units(mut self, units: Units) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Topic and payload of the state of the city, failing if the
+    /// observation has no city ID.
+    pub fn state(&self, info: &WeatherInfo) -> Result<(String, Vec<u8>)> {
+        let id = city_id(info)?;
+        let topic = self.state_topic
+            .replace("{city_id}", &id.to_string())
+            .replace("{city}", &slug(info.name.as_ref().map_or("", String::as_str)));
+
+        Ok((topic, data::to_json(info).to_string().into_bytes()))
+    }
+
+    /// Topics and payloads of the discovery messages of the sensors of the city.
+    pub fn discovery(&self, info: &WeatherInfo) -> Result<Vec<(String, Vec<u8>)>> {
+        let id = city_id(info)?;
+        let (state_topic, _) = self.state(info)?;
+        let device = json!({
+            "identifiers": [format!("owm_{}", id)],
+            "name": info.name.clone().unwrap_or_else(|| id.to_string()),
+            "manufacturer": "OpenWeatherMap",
+        });
+
+        Ok(SENSORS.iter()
+            .map(|&(key, name, template, class)| {
+                let mut config = json!({
+                    "name": name,
+                    "unique_id": format!("owm_{}_{}", id, key),
+                    "state_topic": state_topic,
+                    "value_template": template,
+                    "device": device,
+                });
+                if let Some(class) = class {
+                    config["device_class"] = json!(class);
+                }
+                if let Some(unit) = self.unit(key) {
+                    config["unit_of_measurement"] = json!(unit);
+                    config["state_class"] = json!("measurement");
+                }
+
+                let topic = format!("{}/sensor/owm_{}/{}/config", self.discovery_prefix, id, key);
+                (topic, config.to_string().into_bytes())
+            })
+            .collect())
+    }
+
+    fn unit(&self, sensor: &str) -> Option<&'static str> {
+        Some(match sensor {
+            "temperature" => {
+                match self.units {
+                    Units::Standard => "K",
+                    Units::Metric => "°C",
+                    Units::Imperial => "°F",
+                }
+            }
+            "humidity" => "%",
+            "pressure" => "hPa",
+            "wind_speed" => {
+                match self.units {
+                    Units::Standard | Units::Metric => "m/s",
+                    Units::Imperial => "mph",
+                }
+            }
+            "wind_bearing" => "°",
+            _ => return None,
+        })
+    }
+}
+
+impl Default for Messages {
+    fn default() -> Messages {
+        Messages::new()
+    }
+}
+
+/// Number of published messages not yet delivered: sent for QoS 0,
+/// acknowledged by the broker otherwise.
+#[derive(Default)]
+struct Pending {
+    count: Mutex<usize>,
+    delivered: Condvar,
+}
+
+impl Pending {
+    fn add(&self) {
+        *self.count.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+    }
+
+    fn deliver(&self) {
+        let mut count = self.count.lock().unwrap_or_else(|e| e.into_inner());
+        *count = count.saturating_sub(1);
+        self.delivered.notify_all();
+    }
+
+    /// Waits until all the messages are delivered, returning how many are
+    /// left when the timeout expires.
+    fn wait(&self, timeout: Duration) -> usize {
+        let deadline = Instant::now() + timeout;
+        let mut count = self.count.lock().unwrap_or_else(|e| e.into_inner());
+        while *count > 0 {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left == Duration::from_secs(0) {
+                break;
+            }
+            count = self.delivered.wait_timeout(count, left).unwrap_or_else(|e| e.into_inner()).0;
+        }
+        *count
+    }
+}
+
+/// Connection to a broker, publishing observations.
+pub struct Publisher {
+    client: Client,
+    messages: Messages,
+    qos: QoS,
+    retain: bool,
+    announced: HashSet<i64>,
+    error: Arc<Mutex<Option<String>>>,
+    closing: Arc<AtomicBool>,
+    pending: Arc<Pending>,
+    events: Option<JoinHandle<()>>,
+}
+
+impl Publisher {
+    /// Connects to the broker, waiting for it to accept the connection.
+    pub fn connect(options: MqttOptions, messages: Messages) -> Result<Publisher> {
+        let (client, mut connection) = Client::new(options, QUEUE_CAPACITY);
+        let error = Arc::new(Mutex::new(None));
+        let closing = Arc::new(AtomicBool::new(false));
+        let pending = Arc::new(Pending::default());
+        let (connected, outcome) = mpsc::channel();
+
+        let (last_error, stop, delivered) = (error.clone(), closing.clone(), pending.clone());
+        let events = thread::spawn(move || {
+            let mut connected = Some(connected);
+            for event in connection.iter() {
+                match event {
+                    Ok(Event::Incoming(Incoming::ConnAck(_))) => {
+                        if let Some(connected) = connected.take() {
+                            let _ = connected.send(Ok(()));
+                        }
+                    }
+                    Ok(Event::Outgoing(Outgoing::Publish(0))) |
+                    Ok(Event::Incoming(Incoming::PubAck(_))) |
+                    Ok(Event::Incoming(Incoming::PubComp(_))) => delivered.deliver(),
+                    Ok(Event::Outgoing(Outgoing::Disconnect)) => break,
+                    Ok(_) => (),
+                    Err(err) => {
+                        if let Some(connected) = connected.take() {
+                            let _ = connected.send(Err(err.to_string()));
+                            break;
+                        }
+                        *last_error.lock().unwrap_or_else(|e| e.into_inner()) = Some(err.to_string());
+                        if stop.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        thread::sleep(RECONNECT_DELAY);
+                    }
+                }
+            }
+        });
+
+        match outcome.recv() {
+            Ok(Ok(())) => (),
+            Ok(Err(err)) => return Err(Error::MqttError(err)),
+            Err(_) => return Err(Error::MqttError("connection closed".to_string())),
+        }
+
+        Ok(Publisher {
+            client,
+            messages,
+            qos: QoS::AtLeastOnce,
+            retain: false,
+            announced: HashSet::new(),
+            error,
+            closing,
+            pending,
+            events: Some(events),
+        })
+    }
+
+    /// Change the quality of service of the messages. Default is at least once.
+    pub fn qos(mut self, qos: QoS) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Retain the state messages, so that subscribers get the last state
+    /// when subscribing. Discovery messages are always retained.
+    pub fn retain(mut self, retain: bool) -> Self {
+        self.retain = retain;
+        self
+    }
+
+    /// Queues the discovery messages of the city if it wasn't published
+    /// before, then its state. Fails with the last connection error since
+    /// the previous call, as the connection is re-established in the
+    /// background.
+    pub fn publish(&mut self, info: &WeatherInfo) -> Result<()> {
+        if let Some(err) = self.error.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            return Err(Error::MqttError(err));
+        }
+
+        let (topic, payload) = self.messages.state(info)?;
+        if let Some(id) = info.id.filter(|id| !self.announced.contains(id)) {
+            for (topic, config) in self.messages.discovery(info)? {
+                self.send(topic, true, config)?;
+            }
+            self.announced.insert(id);
+        }
+
+        self.send(topic, self.retain, payload)
+    }
+
+    /// Waits for the queued messages to be delivered, for at most 10
+    /// seconds, then disconnects. Fails if some messages were not delivered.
+    pub fn disconnect(mut self) -> Result<()> {
+        self.close()
+    }
+
+    fn send(&self, topic: String, retain: bool, payload: Vec<u8>) -> Result<()> {
+        self.pending.add();
+        self.client.publish(topic, self.qos, retain, payload).map_err(|err| {
+            self.pending.deliver();
+            mqtt(err)
+        })
+    }
+
+    fn close(&mut self) -> Result<()> {
+        let events = match self.events.take() {
+            Some(events) => events,
+            None => return Ok(()),
+        };
+        let undelivered = self.pending.wait(DRAIN_TIMEOUT);
+
+        self.closing.store(true, Ordering::SeqCst);
+        let sent = self.client.disconnect().map_err(mqtt);
+        let _ = events.join();
+
+        match undelivered {
+            0 => sent,
+            n => Err(Error::MqttError(format!("{} messages not delivered before disconnecting", n))),
+        }
+    }
+}
+
+impl Drop for Publisher {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+fn city_id(info: &WeatherInfo) -> Result<i64> {
+    info.id.ok_or_else(|| Error::InvalidObservation("observation without city ID".to_string()))
+}
+
+/// Lowercase name with underscores, usable in topics.
+fn slug(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn mqtt(err: ::rumqttc::ClientError) -> Error {
+    Error::MqttError(err.to_string())
+}
@@ -0,0 +1,121 @@
+//! Observations and hub statistics in the Prometheus text exposition format.
+//!
+//! Weather gauges are labelled by the name of the tracked location and by the
+//! ID and name of the city, and assume observations fetched in metric units:
+//!
+//! ```text
+//! owm_temperature_celsius{location="home",city_id="2643743",city="London"} 9.4
+//! ```
+
+use std::io::{self, Write};
+use data::{Field, WeatherInfo};
+use {ErrorKind, HubStats};
+
+/// Name, help and value of a weather gauge.
+type Gauge = (&'static str, &'static str, fn(&WeatherInfo) -> Option<String>);
+
+const GAUGES: [Gauge; 12] = [
+    ("owm_temperature_celsius", "Temperature", |i| field(i, Field::Temperature)),
+    ("owm_feels_like_celsius",
+     "Temperature accounting for the human perception of weather",
+     |i| field(i, Field::FeelsLike)),
+    ("owm_humidity_percent", "Relative humidity", |i| field(i, Field::Humidity)),
+    ("owm_pressure_hpa", "Atmospheric pressure", |i| field(i, Field::Pressure)),
+    ("owm_wind_speed_meters_per_second", "Wind speed", |i| field(i, Field::WindSpeed)),
+    ("owm_wind_gust_meters_per_second", "Wind gust", |i| field(i, Field::WindGust)),
+    ("owm_wind_direction_degrees",
+     "Direction the wind comes from",
+     |i| i.wind.as_ref()?.deg.map(|deg| deg.to_string())),
+    ("owm_clouds_percent", "Cloudiness", |i| field(i, Field::Cloudiness)),
+    ("owm_visibility_meters", "Visibility", |i| field(i, Field::Visibility)),
+    ("owm_rain_1h_millimeters",
+     "Rain volume over the last hour, 0 when not reported",
+     |i| field(i, Field::Rain).or_else(|| Some("0".to_string()))),
+    ("owm_snow_1h_millimeters",
+     "Snow volume over the last hour, 0 when not reported",
+     |i| field(i, Field::Snow).or_else(|| Some("0".to_string()))),
+    ("owm_observation_timestamp_seconds",
+     "Time of the observation, unix, UTC",
+     |i| i.dt.map(|dt| dt.to_string())),
+];
+
+/// Writes the weather gauges of the observations of tracked locations.
+pub fn write_weather<'i, W, I>(out: &mut W, observations: I) -> io::Result<()>
+    where W: Write,
+          I: IntoIterator<Item = (&'i str, &'i WeatherInfo)>
+{
+    let observations: Vec<(&str, &WeatherInfo)> = observations.into_iter().collect();
+
+    for &(name, help, value) in GAUGES.iter() {
+        header(out, name, help, "gauge")?;
+
+        for &(location, info) in &observations {
+            if let Some(value) = value(info) {
+                let id = info.id.map(|id| id.to_string()).unwrap_or_default();
+                let city = info.name.as_ref().map_or("", String::as_str);
+                writeln!(out,
+                         "{}{{location=\"{}\",city_id=\"{}\",city=\"{}\"}} {}",
+                         name,
+                         escape(location),
+                         id,
+                         escape(city),
+                         value)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes whether the last query of each tracked location succeeded.
+pub fn write_location_up<'i, W, I>(out: &mut W, locations: I) -> io::Result<()>
+    where W: Write,
+          I: IntoIterator<Item = (&'i str, bool)>
+{
+    header(out, "owm_location_up", "Whether the last query of the location succeeded", "gauge")?;
+    for (location, up) in locations {
+        writeln!(out, "owm_location_up{{location=\"{}\"}} {}", escape(location), up as u8)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the statistics of a hub, with a zero for the kinds of errors that
+/// didn't happen so that rates can be computed.
+pub fn write_stats<W: Write>(out: &mut W, stats: &HubStats) -> io::Result<()> {
+    header(out, "owm_api_requests_total", "Requests sent to the API", "counter")?;
+    writeln!(out, "owm_api_requests_total {}", stats.requests)?;
+
+    header(out,
+           "owm_api_request_duration_seconds",
+           "Time spent waiting for the API to respond",
+           "summary")?;
+    writeln!(out, "owm_api_request_duration_seconds_sum {}", stats.latency.as_secs_f64())?;
+    writeln!(out, "owm_api_request_duration_seconds_count {}", stats.requests)?;
+
+    header(out, "owm_api_errors_total", "Failed queries, by kind of error", "counter")?;
+    for kind in ErrorKind::ALL.iter() {
+        writeln!(out,
+                 "owm_api_errors_total{{kind=\"{}\"}} {}",
+                 kind.name(),
+                 stats.errors.get(kind).cloned().unwrap_or(0))?;
+    }
+
+    header(out, "owm_cache_hits_total", "Queries answered from the cache", "counter")?;
+    writeln!(out, "owm_cache_hits_total {}", stats.cache_hits)
+}
+
+fn header<W: Write>(out: &mut W, name: &str, help: &str, kind: &str) -> io::Result<()> {
+    writeln!(out, "# HELP {} {}", name, help)?;
+    writeln!(out, "# TYPE {} {}", name, kind)
+}
+
+/// Value of a field, in the shortest form of the f32 received from the API.
+fn field(info: &WeatherInfo, field: Field) -> Option<String> {
+    field.value(info).map(|value| (value as f32).to_string())
+}
+
+/// Escapes a label value.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,33 @@
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Spaces out requests so that no more than a given number are sent per minute.
+pub struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_minute: u32) -> RateLimiter {
+        RateLimiter {
+            interval: Duration::from_secs(60) / requests_per_minute.max(1),
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Blocks until the next request is allowed to go out.
+    pub fn wait(&self) {
+        let now = Instant::now();
+        let slot = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let slot = next.map_or(now, |next| next.max(now));
+            *next = Some(slot + self.interval);
+            slot
+        };
+
+        if slot > now {
+            thread::sleep(slot - now);
+        }
+    }
+}
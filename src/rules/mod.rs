@@ -0,0 +1,483 @@
+//! Alert rules evaluated against a stream of observations.
+//!
+//! A [Rule](struct.Rule.html) fires when its [Condition](struct.Condition.html)
+//! holds, optionally for a number of consecutive readings or a duration, and
+//! resolves when it stops holding, or when a separate resolve condition holds
+//! to provide hysteresis. A cooldown keeps a rule from firing again too soon.
+//!
+//! Conditions are written in a small expression language:
+//!
+//! ```text
+//! wind_gust > 20
+//! temp < 0 and rain present
+//! not (clouds >= 50 or visibility < 1000)
+//! ```
+//!
+//! Fields are compared to numbers with `<`, `<=`, `>`, `>=`, `==` and `!=`,
+//! and `present` tests whether the observation includes a field. Comparisons
+//! involving a missing field are false. Comparisons are combined with `and`,
+//! `or`, `not` and parentheses. The fields are:
+//!
+//! * `temp`, `feels_like`, `temp_min`, `temp_max`, `pressure`, `humidity`
+//! * `wind_speed`, `wind_gust`, `wind_deg`, `clouds`, `visibility`
+//! * `rain`, `snow`: volume over the last hour, or the last 3 hours when only
+//!   that one is reported
+//! * `condition`: OWM condition code, e.g. 500 for light rain
+//! * `dew_point`, `heat_index`, `wind_chill`, `humidex`, `apparent_temperature`,
+//!   `absolute_humidity`: see [derived](../derived/index.html)
+//!
+//! Values are expressed in the units the observations were fetched in.
+//! Durations and cooldowns are measured with the `dt` of the observations.
+
+use std::collections::HashMap;
+use std::time::Duration;
+use data::WeatherInfo;
+use {Error, Result, Units};
+
+const FIELDS: [&str; 20] = ["temp", "feels_like", "temp_min", "temp_max", "pressure", "humidity",
+                            "wind_speed", "wind_gust", "wind_deg", "clouds", "visibility",
+                            "rain", "snow", "condition", "dew_point", "heat_index",
+                            "wind_chill", "humidex", "apparent_temperature",
+                            "absolute_humidity"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Compare(&'static str, Op, f64),
+    Present(&'static str),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Op(Op),
+    Open,
+    Close,
+}
+
+/// Boolean expression over the fields of an observation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    expr: Expr,
+}
+
+impl Condition {
+    /// Parses an expression, e.g. "temp < 0 and rain present".
+    pub fn parse(text: &str) -> Result<Condition> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            text,
+            tokens: &tokens,
+            pos: 0,
+        };
+
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(Condition { expr }),
+            Some(&(ref token, at)) => Err(invalid(text, at, &format!("unexpected {:?}", token))),
+        }
+    }
+
+    /// Whether the observation, fetched in the given units, satisfies the condition.
+    pub fn eval(&self, info: &WeatherInfo, units: Units) -> bool {
+        eval(&self.expr, info, units)
+    }
+}
+
+fn eval(expr: &Expr, info: &WeatherInfo, units: Units) -> bool {
+    match *expr {
+        Expr::Compare(field, op, rhs) => {
+            value(field, info, units).is_some_and(|lhs| match op {
+                Op::Lt => lhs < rhs,
+                Op::Le => lhs <= rhs,
+                Op::Gt => lhs > rhs,
+                Op::Ge => lhs >= rhs,
+                Op::Eq => lhs == rhs,
+                Op::Ne => lhs != rhs,
+            })
+        }
+        Expr::Present(field) => value(field, info, units).is_some(),
+        Expr::Not(ref expr) => !eval(expr, info, units),
+        Expr::And(ref lhs, ref rhs) => eval(lhs, info, units) && eval(rhs, info, units),
+        Expr::Or(ref lhs, ref rhs) => eval(lhs, info, units) || eval(rhs, info, units),
+    }
+}
+
+fn value(field: &str, info: &WeatherInfo, units: Units) -> Option<f64> {
+    let main = || info.main.as_ref();
+    let wind = || info.wind.as_ref();
+    let derived = info.derived(units);
+
+    let value = match field {
+        "temp" => main()?.temp?,
+        "feels_like" => main()?.feels_like?,
+        "temp_min" => main()?.temp_min?,
+        "temp_max" => main()?.temp_max?,
+        "pressure" => main()?.pressure?,
+        "humidity" => main()?.humidity? as f32,
+        "wind_speed" => wind()?.speed?,
+        "wind_gust" => wind()?.gust?,
+        "wind_deg" => wind()?.deg?,
+        "clouds" => info.clouds.as_ref()?.all? as f32,
+        "visibility" => info.visibility? as f32,
+        "rain" => info.rain.as_ref().and_then(|rain| rain.one_hour.or(rain.three_hours))?,
+        "snow" => info.snow.as_ref().and_then(|snow| snow.one_hour.or(snow.three_hours))?,
+        "condition" => info.weather.as_ref()?.first()?.id? as f32,
+        "dew_point" => derived.dew_point()?,
+        "heat_index" => derived.heat_index()?,
+        "wind_chill" => derived.wind_chill()?,
+        "humidex" => derived.humidex()?,
+        "apparent_temperature" => derived.apparent_temperature()?,
+        "absolute_humidity" => derived.absolute_humidity()?,
+        _ => return None,
+    };
+
+    Some(f64::from(value))
+}
+
+/// Splits an expression into tokens, with their byte offsets.
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(at, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' | ')' => {
+                chars.next();
+                if c == '(' { Token::Open } else { Token::Close }
+            }
+            '<' | '>' | '=' | '!' => {
+                chars.next();
+                let equals = chars.next_if(|&(_, c)| c == '=').is_some();
+                Token::Op(match (c, equals) {
+                    ('<', false) => Op::Lt,
+                    ('<', true) => Op::Le,
+                    ('>', false) => Op::Gt,
+                    ('>', true) => Op::Ge,
+                    ('=', true) => Op::Eq,
+                    ('!', true) => Op::Ne,
+                    _ => return Err(invalid(text, at, &format!("unknown operator {}", c))),
+                })
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let mut number = String::new();
+                while let Some((_, c)) = chars.next_if(|&(i, c)| {
+                    c.is_ascii_digit() || c == '.' || (c == '-' && i == at)
+                }) {
+                    number.push(c);
+                }
+                Token::Number(number.parse()
+                    .map_err(|_| invalid(text, at, &format!("invalid number {}", number)))?)
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut ident = String::new();
+                let word = |&(_, c): &(usize, char)| c.is_ascii_alphanumeric() || c == '_';
+                while let Some((_, c)) = chars.next_if(word) {
+                    ident.push(c);
+                }
+                Token::Ident(ident)
+            }
+            c => return Err(invalid(text, at, &format!("unexpected character {}", c))),
+        };
+
+        tokens.push((token, at));
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser, with `or` binding looser than `and`, and `and`
+/// looser than `not`.
+struct Parser<'t> {
+    text: &'t str,
+    tokens: &'t [(Token, usize)],
+    pos: usize,
+}
+
+impl<'t> Parser<'t> {
+    fn peek(&self) -> Option<&'t (Token, usize)> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&'t (Token, usize)> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    /// Consumes the next token if it is the given keyword.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(&(Token::Ident(ref ident), _)) if ident == keyword => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Offset of the next token, for error messages.
+    fn at(&self) -> usize {
+        self.peek().map_or(self.text.len(), |&(_, at)| at)
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+
+        let at = self.at();
+        match self.next() {
+            Some(&(Token::Open, _)) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(&(Token::Close, _)) => Ok(expr),
+                    _ => Err(invalid(self.text, at, "unclosed parenthesis")),
+                }
+            }
+            Some(&(Token::Ident(ref ident), _)) => {
+                let field = FIELDS.iter()
+                    .find(|&&field| field == ident)
+                    .ok_or_else(|| invalid(self.text, at, &format!("unknown field {}", ident)))?;
+
+                if self.keyword("present") {
+                    return Ok(Expr::Present(field));
+                }
+
+                let at = self.at();
+                match (self.next(), self.next()) {
+                    (Some(&(Token::Op(op), _)), Some(&(Token::Number(value), _))) => {
+                        Ok(Expr::Compare(field, op, value))
+                    }
+                    _ => {
+                        Err(invalid(self.text,
+                                    at,
+                                    &format!("expected a comparison or present after {}", field)))
+                    }
+                }
+            }
+            _ => Err(invalid(self.text, at, "expected a field, not or (")),
+        }
+    }
+}
+
+fn invalid(text: &str, at: usize, reason: &str) -> Error {
+    Error::InvalidRule(format!("{} at position {} in \"{}\"", reason, at, text))
+}
+
+/// Named condition with the timing of its alerts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    name: String,
+    condition: Condition,
+    resolve: Option<Condition>,
+    readings: usize,
+    duration: Duration,
+    cooldown: Duration,
+}
+
+impl Rule {
+    /// Creates a rule firing as soon as the condition holds.
+    pub fn new(name: &str, condition: &str) -> Result<Rule> {
+        Ok(Rule {
+            name: name.to_string(),
+            condition: Condition::parse(condition)?,
+            resolve: None,
+            readings: 1,
+            duration: Duration::from_secs(0),
+            cooldown: Duration::from_secs(0),
+        })
+    }
+
+    /// Name of the rule, as reported in its alerts.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Fire only once the condition held for that many consecutive readings. Default is 1.
+    pub fn for_readings(mut self, readings: usize) -> Rule {
+        self.readings = readings.max(1);
+        self
+    }
+
+    /// Fire only once the condition held for that long. Default is no delay.
+    pub fn for_duration(mut self, duration: Duration) -> Rule {
+        self.duration = duration;
+        self
+    }
+
+    /// Resolve only when the given condition holds, rather than as soon as the
+    /// firing condition stops holding, e.g. "wind_gust < 15" for a rule
+    /// firing on "wind_gust > 20".
+    pub fn resolve_when(mut self, condition: &str) -> Result<Rule> {
+        self.resolve = Some(Condition::parse(condition)?);
+        Ok(self)
+    }
+
+    /// Don't fire again within that time of the previous alert. Default is none.
+    pub fn cooldown(mut self, cooldown: Duration) -> Rule {
+        self.cooldown = cooldown;
+        self
+    }
+}
+
+/// Whether an alert started or ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertState {
+    Firing,
+    Resolved,
+}
+
+/// Start or end of an alert, for a rule and a city.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub rule: String,
+    pub state: AlertState,
+    /// City ID of the observation
+    pub city_id: Option<i64>,
+    /// City name of the observation
+    pub city: Option<String>,
+    /// Time of the observation, unix, UTC
+    pub dt: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    firing: bool,
+    readings: usize,
+    since: Option<i64>,
+    last_fired: Option<i64>,
+}
+
+/// Evaluates rules against observations, keeping track of each city
+/// separately, by city ID.
+pub struct RuleEngine {
+    rules: Vec<Rule>,
+    units: Units,
+    states: HashMap<(usize, Option<i64>), State>,
+}
+
+impl RuleEngine {
+    /// Creates an engine for observations fetched in the given units.
+    pub fn new(units: Units) -> RuleEngine {
+        RuleEngine {
+            rules: Vec::new(),
+            units,
+            states: HashMap::new(),
+        }
+    }
+
+    /// Add a rule to evaluate.
+    pub fn rule(mut self, rule: Rule) -> RuleEngine {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Names of the rules currently firing for the given city.
+    pub fn firing(&self, city_id: Option<i64>) -> Vec<&str> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| self.states.get(&(i, city_id)).is_some_and(|state| state.firing))
+            .map(|(_, rule)| rule.name())
+            .collect()
+    }
+
+    /// Evaluates the rules against the next observation of a city, which
+    /// should be more recent than the previous one, and returns the alerts
+    /// that started or ended, in the order the rules were added.
+    pub fn observe(&mut self, info: &WeatherInfo) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+
+        for (i, rule) in self.rules.iter().enumerate() {
+            let state = self.states.entry((i, info.id)).or_default();
+            let holds = rule.condition.eval(info, self.units);
+
+            let change = if state.firing {
+                let resolved = match rule.resolve {
+                    Some(ref resolve) => resolve.eval(info, self.units),
+                    None => !holds,
+                };
+                if resolved {
+                    state.firing = false;
+                    state.readings = 0;
+                    state.since = None;
+                    Some(AlertState::Resolved)
+                } else {
+                    None
+                }
+            } else if holds {
+                state.readings += 1;
+                if state.readings == 1 {
+                    state.since = info.dt;
+                }
+
+                let held = elapsed(state.since, info.dt).unwrap_or_default();
+                let cooling = elapsed(state.last_fired, info.dt)
+                    .is_some_and(|elapsed| elapsed < rule.cooldown);
+
+                if state.readings >= rule.readings && held >= rule.duration && !cooling {
+                    state.firing = true;
+                    state.last_fired = info.dt;
+                    Some(AlertState::Firing)
+                } else {
+                    None
+                }
+            } else {
+                state.readings = 0;
+                state.since = None;
+                None
+            };
+
+            if let Some(state) = change {
+                alerts.push(Alert {
+                    rule: rule.name.clone(),
+                    state,
+                    city_id: info.id,
+                    city: info.name.clone(),
+                    dt: info.dt,
+                });
+            }
+        }
+
+        alerts
+    }
+}
+
+/// Time between two observation timestamps, if both are known.
+fn elapsed(from: Option<i64>, to: Option<i64>) -> Option<Duration> {
+    Some(Duration::from_secs((to? - from?).max(0) as u64))
+}
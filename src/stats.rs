@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use ErrorKind;
+
+/// Counters of the queries run by a [WeatherHub](struct.WeatherHub.html),
+/// since its creation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HubStats {
+    /// Requests sent to the server
+    pub requests: u64,
+    /// Total time spent waiting for the server to respond
+    pub latency: Duration,
+    /// Queries answered from the cache
+    pub cache_hits: u64,
+    /// Failed queries, by kind of error
+    pub errors: HashMap<ErrorKind, u64>,
+}
+
+/// Shared, updatable statistics.
+#[derive(Default)]
+pub struct Recorder {
+    stats: Mutex<HubStats>,
+}
+
+impl Recorder {
+    fn update<F: FnOnce(&mut HubStats)>(&self, f: F) {
+        f(&mut self.stats.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    pub fn request(&self, latency: Duration) {
+        self.update(|stats| {
+            stats.requests += 1;
+            stats.latency += latency;
+        })
+    }
+
+    pub fn cache_hit(&self) {
+        self.update(|stats| stats.cache_hits += 1)
+    }
+
+    pub fn error(&self, kind: ErrorKind) {
+        self.update(|stats| *stats.errors.entry(kind).or_insert(0) += 1)
+    }
+
+    pub fn snapshot(&self) -> HubStats {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
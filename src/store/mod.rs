@@ -0,0 +1,182 @@
+//! History of observations, kept in a local SQLite database.
+//!
+//! Requires the `sqlite` cargo feature. Observations are keyed by city ID
+//! and time of data calculation, so that storing the same observation twice,
+//! e.g. when polling faster than OWM updates its data, keeps a single copy:
+//!
+//! ```no_run
+//! # extern crate hyper;
+//! # extern crate owm;
+//! # use owm::{Location, WeatherHub};
+//! # use owm::data::Field;
+//! # use owm::store::Store;
+//! # fn main() -> owm::Result<()> {
+//! # let hub = WeatherHub::new(hyper::Client::new(), "YOUR_API_KEY");
+//! let mut store = Store::open("weather.db")?;
+//! let (_, info) = hub.current().at(&Location::id(2643743))?;
+//! store.insert(&info)?;
+//!
+//! for day in store.daily(2643743, Field::Temperature, 1560297600..1560556800)? {
+//!     println!("{} {:.1} {:.1}", day.start, day.min, day.max);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::Path;
+use rusqlite::{self, params, Connection, OptionalExtension};
+use json;
+use data::{Field, Stats, WeatherInfo};
+use {Error, Result};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS observations (
+                          city_id INTEGER NOT NULL,
+                          dt INTEGER NOT NULL,
+                          data TEXT NOT NULL,
+                          PRIMARY KEY (city_id, dt)
+                      ) WITHOUT ROWID";
+
+/// Minimum, maximum and mean of a field over a day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyRollup {
+    /// Start of the day in the local time of the city, Unix, UTC
+    pub start: i64,
+    /// Number of observations providing the field
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+/// Database of observations.
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Opens the database at the given path, creating it if needed.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Store> {
+        Store::with_connection(Connection::open(path).map_err(storage)?)
+    }
+
+    /// Creates a database that only lives in memory.
+    pub fn in_memory() -> Result<Store> {
+        Store::with_connection(Connection::open_in_memory().map_err(storage)?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Store> {
+        conn.execute(SCHEMA, []).map_err(storage)?;
+        Ok(Store { conn })
+    }
+
+    /// Stores an observation. Returns false if it was already stored, and
+    /// fails if it lacks a city ID or time.
+    pub fn insert(&mut self, info: &WeatherInfo) -> Result<bool> {
+        insert(&self.conn, info)
+    }
+
+    /// Stores several observations at once, e.g. an aggregate, and returns
+    /// how many were not already stored. Nothing is stored on failure.
+    pub fn insert_all<'a, I>(&mut self, observations: I) -> Result<usize>
+        where I: IntoIterator<Item = &'a WeatherInfo>
+    {
+        let tx = self.conn.transaction().map_err(storage)?;
+        let mut inserted = 0;
+        for info in observations {
+            inserted += insert(&tx, info)? as usize;
+        }
+        tx.commit().map_err(storage)?;
+
+        Ok(inserted)
+    }
+
+    /// Observations of a city calculated in the given range of Unix times,
+    /// from the oldest.
+    pub fn range(&self, city_id: i64, range: Range<i64>) -> Result<Vec<WeatherInfo>> {
+        let mut stmt = self.conn
+            .prepare_cached("SELECT data FROM observations
+                             WHERE city_id = ?1 AND dt >= ?2 AND dt < ?3 ORDER BY dt")
+            .map_err(storage)?;
+        let rows = stmt.query_map(params![city_id, range.start, range.end],
+                       |row| row.get::<_, String>(0))
+            .map_err(storage)?;
+
+        rows.map(|data| decode(&data.map_err(storage)?)).collect()
+    }
+
+    /// Most recent observation of a city, if any.
+    pub fn latest(&self, city_id: i64) -> Result<Option<WeatherInfo>> {
+        let data: Option<String> = self.conn
+            .query_row("SELECT data FROM observations WHERE city_id = ?1 ORDER BY dt DESC LIMIT 1",
+                       [city_id],
+                       |row| row.get::<_, String>(0))
+            .optional()
+            .map_err(storage)?;
+
+        data.map(|data| decode(&data)).transpose()
+    }
+
+    /// Most recent observation of every city, by increasing city ID.
+    pub fn latest_all(&self) -> Result<Vec<WeatherInfo>> {
+        let mut stmt = self.conn
+            .prepare_cached("SELECT data, MAX(dt) FROM observations GROUP BY city_id ORDER BY city_id")
+            .map_err(storage)?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0)).map_err(storage)?;
+
+        rows.map(|data| decode(&data.map_err(storage)?)).collect()
+    }
+
+    /// Daily statistics of a field for a city, over the observations in the
+    /// given range of Unix times. Days follow the local time of the city,
+    /// and days without values of the field are left out.
+    pub fn daily(&self, city_id: i64, field: Field, range: Range<i64>) -> Result<Vec<DailyRollup>> {
+        let mut days: BTreeMap<i64, Vec<f64>> = BTreeMap::new();
+        for info in self.range(city_id, range)? {
+            if let (Some(dt), Some(value)) = (info.dt, field.value(&info)) {
+                let offset = i64::from(info.timezone.unwrap_or(0));
+                let start = (dt + offset).div_euclid(SECONDS_PER_DAY) * SECONDS_PER_DAY - offset;
+                days.entry(start).or_default().push(value);
+            }
+        }
+
+        Ok(days.into_iter()
+            .filter_map(|(start, values)| {
+                let stats = Stats::new(values)?;
+                Some(DailyRollup {
+                    start,
+                    count: stats.count,
+                    min: stats.min,
+                    max: stats.max,
+                    mean: stats.mean,
+                })
+            })
+            .collect())
+    }
+}
+
+fn insert(conn: &Connection, info: &WeatherInfo) -> Result<bool> {
+    let (city_id, dt) = match (info.id, info.dt) {
+        (Some(city_id), Some(dt)) => (city_id, dt),
+        _ => return Err(Error::InvalidObservation("observation without city ID or time".to_string())),
+    };
+    let data = json::to_string(info).map_err(|err| Error::StorageError(err.to_string()))?;
+
+    let inserted = conn.prepare_cached("INSERT OR IGNORE INTO observations (city_id, dt, data)
+                                        VALUES (?1, ?2, ?3)")
+        .and_then(|mut stmt| stmt.execute(params![city_id, dt, data]))
+        .map_err(storage)?;
+
+    Ok(inserted == 1)
+}
+
+fn decode(data: &str) -> Result<WeatherInfo> {
+    json::from_str(data).map_err(|err| Error::StorageError(format!("corrupt observation: {}", err)))
+}
+
+fn storage(err: rusqlite::Error) -> Error {
+    Error::StorageError(err.to_string())
+}
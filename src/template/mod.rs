@@ -0,0 +1,347 @@
+//! One-line rendering of an observation, e.g. for status bars.
+//!
+//! A [Template](struct.Template.html) is text with placeholders replaced by
+//! fields of a `WeatherInfo`, such as `{temp:.0}{temp_units} {condition_icon}`.
+//! Numeric placeholders accept a precision, and literal braces are written
+//! `{{` and `}}`. Placeholders for missing fields are rendered as `-`.
+//!
+//! | Placeholder | Value |
+//! |-------------|-------|
+//! | `city`, `country` | City name and country code |
+//! | `condition`, `description` | Weather group (e.g. "Rain") and description |
+//! | `condition_icon` | Icon of the weather condition, from the selected icon set |
+//! | `temp`, `feels_like`, `temp_min`, `temp_max`, `dew_point` | Temperatures |
+//! | `units`, `speed_units` | Symbols of the temperature and speed units, e.g. `C`, `m/s` |
+//! | `temp_units` | Suffix of temperatures: `°C`, `°F`, or ` K` as kelvins take no degree sign |
+//! | `humidity`, `pressure`, `clouds`, `visibility` | In %, hPa, % and m |
+//! | `wind_speed`, `wind_gust`, `wind_deg` | Wind |
+//! | `wind_compass`, `wind_beaufort` | Wind direction (e.g. `NNE`) and Beaufort number |
+//! | `rain`, `snow` | Precipitation over the last hour, in mm |
+
+use data::{CompassPoints, WeatherInfo};
+use {Error, Result, Units};
+
+/// Template of the tooltips of the status bar blocks.
+pub const DEFAULT_TOOLTIP: &str = "{city}: {description}, feels like {feels_like:.0}{temp_units}\n\
+                                   Wind {wind_speed:.1} {speed_units} {wind_compass}, \
+                                   humidity {humidity}%";
+
+const FIELDS: [&str; 24] = ["city", "country", "condition", "description", "condition_icon",
+                            "temp", "feels_like", "temp_min", "temp_max", "dew_point", "units",
+                            "temp_units", "speed_units", "humidity", "pressure", "clouds",
+                            "visibility", "wind_speed", "wind_gust", "wind_deg", "wind_compass",
+                            "wind_beaufort", "rain", "snow"];
+
+/// Set of icons used for the `condition_icon` placeholder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconSet {
+    /// Unicode emoji, displayed by most fonts
+    Emoji,
+    /// Weather glyphs of the patched Nerd Fonts
+    NerdFont,
+    /// Glyphs of the Weather Icons font
+    WeatherIcons,
+}
+
+impl IconSet {
+    /// Icon of the given OWM condition code, by day or night.
+    pub fn icon(&self, condition: i32, night: bool) -> &'static str {
+        let group = match condition {
+            200..=299 => 0,
+            300..=399 => 1,
+            511 | 600..=699 => 3,
+            500..=599 => 2,
+            781 => 5,
+            700..=799 => 4,
+            800 if night => 7,
+            800 => 6,
+            801 | 802 if night => 9,
+            801 | 802 => 8,
+            _ => 10,
+        };
+
+        match *self {
+            IconSet::Emoji => {
+                ["⛈", "🌦", "🌧", "🌨", "🌫", "🌪", "☀", "🌙", "⛅", "☁", "☁"][group]
+            }
+            IconSet::NerdFont => {
+                ["\u{e31d}", "\u{e31b}", "\u{e318}", "\u{e31a}", "\u{e313}", "\u{e351}",
+                 "\u{e30d}", "\u{e32b}", "\u{e302}", "\u{e37e}", "\u{e312}"][group]
+            }
+            IconSet::WeatherIcons => {
+                ["\u{f01e}", "\u{f01c}", "\u{f019}", "\u{f01b}", "\u{f014}", "\u{f056}",
+                 "\u{f00d}", "\u{f02e}", "\u{f002}", "\u{f086}", "\u{f013}"][group]
+            }
+        }
+    }
+}
+
+/// How much attention the weather conditions deserve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Clear or cloudy skies, drizzle, mist
+    Normal,
+    /// Rain, snow, thunderstorms, fog and other reduced visibility
+    Warning,
+    /// Heavy or freezing precipitation, violent storms, squalls, tornadoes
+    Critical,
+}
+
+impl Severity {
+    /// Severity of the given OWM condition code.
+    pub fn of(condition: i32) -> Severity {
+        match condition {
+            202 | 212 | 221 | 232 | 502..=504 | 511 | 522 | 602 | 622 | 762 | 771 | 781 => {
+                Severity::Critical
+            }
+            200..=299 | 500..=699 | 700..=799 if condition != 701 && condition != 721 => {
+                Severity::Warning
+            }
+            _ => Severity::Normal,
+        }
+    }
+
+    /// CSS class of the severity, as used by waybar.
+    pub fn class(&self) -> &'static str {
+        match *self {
+            Severity::Normal => "normal",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Field(&'static str, Option<usize>),
+}
+
+/// Text with placeholders for the fields of an observation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+    units: Units,
+    icons: IconSet,
+}
+
+impl Template {
+    /// Parses a template, failing on unknown placeholders and unbalanced braces.
+    pub fn parse(text: &str) -> Result<Template> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut spec = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        spec.push(c);
+                    }
+                    if !closed || spec.contains('{') {
+                        return Err(invalid(text, "unclosed placeholder"));
+                    }
+
+                    if !literal.is_empty() {
+                        parts.push(Part::Text(literal.split_off(0)));
+                    }
+                    parts.push(placeholder(text, &spec)?);
+                }
+                '}' => return Err(invalid(text, "unmatched }")),
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Text(literal));
+        }
+
+        Ok(Template {
+            parts,
+            units: Units::Standard,
+            icons: IconSet::Emoji,
+        })
+    }
+
+    /// Change the units the observations were fetched in. Default is Standard.
+    pub fn units(mut self, units: Units) -> Template {
+        self.units = units;
+        self
+    }
+
+    /// Change the icons of the `condition_icon` placeholder. Default is Emoji.
+    pub fn icons(mut self, icons: IconSet) -> Template {
+        self.icons = icons;
+        self
+    }
+
+    /// Replaces the placeholders with the fields of the observation.
+    pub fn render(&self, info: &WeatherInfo) -> String {
+        self.parts
+            .iter()
+            .map(|part| match *part {
+                Part::Text(ref text) => text.clone(),
+                Part::Field(name, precision) => {
+                    match self.value(name, info) {
+                        Some(Value::Number(n)) => {
+                            match precision {
+                                Some(precision) => format!("{:.*}", precision, n),
+                                None => n.to_string(),
+                            }
+                        }
+                        Some(Value::Text(text)) => text,
+                        None => "-".to_string(),
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Block of the i3bar protocol, colored according to the severity of the
+    /// weather conditions.
+    pub fn i3bar(&self, info: &WeatherInfo) -> json::Value {
+        let mut block = json!({
+            "name": "weather",
+            "full_text": self.render(info),
+        });
+
+        match severity(info) {
+            Some(Severity::Warning) => block["color"] = json!("#FFCC00"),
+            Some(Severity::Critical) => {
+                block["color"] = json!("#FF5555");
+                block["urgent"] = json!(true);
+            }
+            _ => (),
+        }
+
+        block
+    }
+
+    /// Output of a waybar custom module, whose class is the severity of the
+    /// weather conditions and whose `alt` is the weather group, e.g. "rain".
+    pub fn waybar(&self, info: &WeatherInfo) -> json::Value {
+        let tooltip = Template::parse(DEFAULT_TOOLTIP)
+            .expect("default tooltip is valid")
+            .units(self.units)
+            .icons(self.icons);
+
+        json!({
+            "text": self.render(info),
+            "tooltip": tooltip.render(info),
+            "class": severity(info).unwrap_or(Severity::Normal).class(),
+            "alt": condition(info).and_then(|c| c.main.clone()).unwrap_or_default().to_lowercase(),
+        })
+    }
+
+    fn value(&self, name: &str, info: &WeatherInfo) -> Option<Value> {
+        let main = || info.main.as_ref();
+        let wind = || info.wind.as_ref();
+        let number = |n: f32| Some(Value::Number(n));
+        let text = |t: &str| Some(Value::Text(t.to_string()));
+
+        match name {
+            "city" => text(info.name.as_ref()?),
+            "country" => text(info.sys.as_ref()?.country.as_ref()?),
+            "condition" => text(condition(info)?.main.as_ref()?),
+            "description" => text(condition(info)?.description.as_ref()?),
+            "condition_icon" => {
+                let condition = condition(info)?;
+                let night = condition.icon.as_ref().is_some_and(|icon| icon.ends_with('n'));
+                text(self.icons.icon(condition.id?, night))
+            }
+            "temp" => number(main()?.temp?),
+            "feels_like" => number(main()?.feels_like?),
+            "temp_min" => number(main()?.temp_min?),
+            "temp_max" => number(main()?.temp_max?),
+            "dew_point" => number(info.derived(self.units).dew_point()?),
+            "units" => {
+                text(match self.units {
+                    Units::Standard => "K",
+                    Units::Metric => "C",
+                    Units::Imperial => "F",
+                })
+            }
+            "temp_units" => {
+                text(match self.units {
+                    Units::Standard => " K",
+                    Units::Metric => "°C",
+                    Units::Imperial => "°F",
+                })
+            }
+            "speed_units" => {
+                text(match self.units {
+                    Units::Standard | Units::Metric => "m/s",
+                    Units::Imperial => "mph",
+                })
+            }
+            "humidity" => number(main()?.humidity? as f32),
+            "pressure" => number(main()?.pressure?),
+            "clouds" => number(info.clouds.as_ref()?.all? as f32),
+            "visibility" => number(info.visibility? as f32),
+            "wind_speed" => number(wind()?.speed?),
+            "wind_gust" => number(wind()?.gust?),
+            "wind_deg" => number(wind()?.deg?),
+            "wind_compass" => text(wind()?.compass(CompassPoints::Sixteen)?),
+            "wind_beaufort" => number(f32::from(wind()?.beaufort(self.units)?)),
+            "rain" => number(info.rain.as_ref()?.one_hour?),
+            "snow" => number(info.snow.as_ref()?.one_hour?),
+            _ => None,
+        }
+    }
+}
+
+enum Value {
+    Number(f32),
+    Text(String),
+}
+
+/// Severity of the main weather condition of the observation.
+pub fn severity(info: &WeatherInfo) -> Option<Severity> {
+    condition(info)?.id.map(Severity::of)
+}
+
+fn condition(info: &WeatherInfo) -> Option<&::data::Weather> {
+    info.weather.as_ref()?.first()
+}
+
+/// Parses the content of a placeholder, e.g. "temp:.1".
+fn placeholder(text: &str, spec: &str) -> Result<Part> {
+    let (name, format) = match spec.find(':') {
+        Some(i) => (&spec[..i], Some(&spec[i + 1..])),
+        None => (spec, None),
+    };
+
+    let name = FIELDS.iter()
+        .find(|&&field| field == name.trim())
+        .ok_or_else(|| invalid(text, &format!("unknown placeholder {{{}}}", name)))?;
+
+    let precision = match format {
+        None => None,
+        Some(format) => {
+            let digits = format.strip_prefix('.')
+                .ok_or_else(|| invalid(text, &format!("invalid format :{}", format)))?;
+            Some(digits.parse()
+                .map_err(|_| invalid(text, &format!("invalid precision .{}", digits)))?)
+        }
+    };
+
+    Ok(Part::Field(name, precision))
+}
+
+fn invalid(template: &str, reason: &str) -> Error {
+    Error::InvalidTemplate(format!("{} in \"{}\"", reason, template))
+}
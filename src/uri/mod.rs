@@ -1,8 +1,13 @@
 use ::*;
 use std::collections::HashMap;
 
+/// Base URL of the OWM API.
+pub const DEFAULT_BASE_URL: &str = "http://api.openweathermap.org/data";
+
 /// Generic URI builder that handles all URI-related stuff.
+#[derive(Clone)]
 pub struct UriBuilder<'a> {
+    base: &'a str,
     api_ver: &'a str,
     method: &'a str,
     params: HashMap<&'a str, String>,
@@ -14,8 +19,9 @@ pub trait HasBuilder<'a> {
 }
 
 impl<'a> UriBuilder<'a> {
-    pub fn new() -> Self {
+    pub fn new(base: &'a str) -> Self {
         UriBuilder {
+            base: base,
             api_ver: "2.5",
             method: "",
             params: HashMap::with_capacity(10),
@@ -36,7 +42,8 @@ impl<'a> UriBuilder<'a> {
 
     /// Consumes the builder and returns the corresponding URI.
     pub fn build(&mut self) -> String {
-        let base = format!("http://api.openweathermap.org/data/{api}/{method}",
+        let base = format!("{base}/{api}/{method}",
+                           base = self.base,
                            api = self.api_ver,
                            method = self.method);
         let mut ser = url::form_urlencoded::Serializer::new(String::new());
@@ -0,0 +1,312 @@
+//! Periodic polling of locations, reporting how their weather changes.
+//!
+//! A [Watcher](struct.Watcher.html) queries the current weather of its
+//! locations at a fixed interval and compares every observation with the
+//! previous one of the same location. Differences are delivered as
+//! [Event](struct.Event.html)s, either returned by [poll](struct.Watcher.html#method.poll)
+//! or passed to a callback by [run](struct.Watcher.html#method.run).
+//!
+//! Time is read from a [Clock](trait.Clock.html), so that tests can use a
+//! [ManualClock](struct.ManualClock.html) instead of waiting for real.
+
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use data::{Field, Weather, WeatherInfo};
+use {ErrorKind, Location, WeatherHub};
+
+/// Default polling interval, matching how often OWM updates its data.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Source of time of a watcher.
+pub trait Clock {
+    fn now(&self) -> Instant;
+
+    /// Waits for the given duration.
+    fn sleep(&self, duration: Duration);
+}
+
+/// The system's monotonic clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration)
+    }
+}
+
+/// Clock that only moves when told to, or when slept on.
+#[derive(Debug)]
+pub struct ManualClock {
+    now: Mutex<Instant>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock { now: Mutex::new(Instant::now()) }
+    }
+
+    /// Moves the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) += duration;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> ManualClock {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Advances the clock without blocking.
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration)
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+}
+
+/// Kind of precipitation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precipitation {
+    Rain,
+    Snow,
+}
+
+impl Precipitation {
+    /// Whether the observation reports this kind of precipitation, either as
+    /// a volume over the last hours or as a weather condition.
+    pub fn is_falling(&self, info: &WeatherInfo) -> bool {
+        let (volume, conditions): (_, &[_]) = match *self {
+            Precipitation::Rain => {
+                (info.rain.as_ref().map(|rain| (rain.one_hour, rain.three_hours)),
+                 &[200..=202, 230..=232, 300..=399, 500..=599])
+            }
+            Precipitation::Snow => {
+                (info.snow.as_ref().map(|snow| (snow.one_hour, snow.three_hours)),
+                 &[600..=699])
+            }
+        };
+
+        let volume = volume.is_some_and(|(one_hour, three_hours)| {
+            one_hour.unwrap_or(0.0) > 0.0 || three_hours.unwrap_or(0.0) > 0.0
+        });
+        let condition = info.weather
+            .iter()
+            .flatten()
+            .filter_map(|weather| weather.id)
+            .any(|id| conditions.iter().any(|range| range.contains(&id)));
+
+        volume || condition
+    }
+}
+
+/// What changed between two observations of a location.
+#[derive(Debug, Clone)]
+pub enum Change {
+    /// The main weather condition has a different code, e.g. from clear sky to rain.
+    ConditionChanged {
+        from: Box<Weather>,
+        to: Box<Weather>,
+    },
+    /// A field went from one side of a threshold to the other. A value equal
+    /// to the threshold counts as above it.
+    ThresholdCrossed {
+        field: Field,
+        threshold: f64,
+        from: f64,
+        to: f64,
+    },
+    PrecipitationStarted(Precipitation),
+    PrecipitationStopped(Precipitation),
+    /// The location could not be queried. Its last observation is kept.
+    QueryFailed(ErrorKind),
+}
+
+/// Change of the weather of a tracked location.
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub location: Location,
+    pub change: Change,
+}
+
+struct Tracked {
+    location: Location,
+    last: Option<WeatherInfo>,
+    due: Option<Instant>,
+}
+
+/// Polls the current weather of a set of locations and reports changes.
+pub struct Watcher<'a, C: Clock = SystemClock> {
+    hub: &'a WeatherHub,
+    clock: C,
+    interval: Duration,
+    thresholds: Vec<(Field, f64)>,
+    tracked: Vec<Tracked>,
+}
+
+impl<'a> Watcher<'a, SystemClock> {
+    /// Creates a watcher querying through the given hub, in the hub's default
+    /// units and language.
+    pub fn new(hub: &'a WeatherHub) -> Watcher<'a, SystemClock> {
+        Watcher::with_clock(hub, SystemClock)
+    }
+}
+
+impl<'a, C: Clock> Watcher<'a, C> {
+    /// Creates a watcher reading time from the given clock.
+    pub fn with_clock(hub: &'a WeatherHub, clock: C) -> Watcher<'a, C> {
+        Watcher {
+            hub,
+            clock,
+            interval: DEFAULT_INTERVAL,
+            thresholds: Vec::new(),
+            tracked: Vec::new(),
+        }
+    }
+
+    /// Change the time between two queries of a location. Default is 10 minutes.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Report when the field crosses the given value, in the units of the hub.
+    pub fn threshold(mut self, field: Field, value: f64) -> Self {
+        self.thresholds.push((field, value));
+        self
+    }
+
+    /// Add a location to watch. It is first queried by the next poll.
+    pub fn track(mut self, location: Location) -> Self {
+        self.tracked.push(Tracked {
+            location,
+            last: None,
+            due: None,
+        });
+        self
+    }
+
+    /// Last observation of a tracked location, if any.
+    pub fn last(&self, location: &Location) -> Option<&WeatherInfo> {
+        self.tracked.iter().find(|t| t.location == *location).and_then(|t| t.last.as_ref())
+    }
+
+    /// Queries the locations that are due and returns the changes since their
+    /// previous observation, in the order the locations were added. The first
+    /// observation of a location only serves as reference.
+    pub fn poll(&mut self) -> Vec<Event> {
+        let now = self.clock.now();
+        let mut events = Vec::new();
+
+        for tracked in &mut self.tracked {
+            if tracked.due.is_some_and(|due| due > now) {
+                continue;
+            }
+            tracked.due = Some(now + self.interval);
+
+            let changes = match self.hub.current().at(&tracked.location) {
+                Ok((_, info)) => {
+                    let changes = match tracked.last {
+                        Some(ref last) => diff(last, &info, &self.thresholds),
+                        None => Vec::new(),
+                    };
+                    tracked.last = Some(info);
+                    changes
+                }
+                Err(err) => vec![Change::QueryFailed(err.kind())],
+            };
+
+            events.extend(changes.into_iter().map(|change| {
+                Event {
+                    location: tracked.location.clone(),
+                    change,
+                }
+            }));
+        }
+
+        events
+    }
+
+    /// Polls forever, sleeping until the next location is due, and passes
+    /// every event to `on_event`. Returns when `on_event` returns false.
+    pub fn run<F>(&mut self, mut on_event: F)
+        where F: FnMut(Event) -> bool
+    {
+        loop {
+            for event in self.poll() {
+                if !on_event(event) {
+                    return;
+                }
+            }
+
+            let next = self.tracked.iter().filter_map(|t| t.due).min();
+            let wait = next.map_or(self.interval, |next| next.saturating_duration_since(self.clock.now()));
+            self.clock.sleep(wait);
+        }
+    }
+
+    /// Like [run](#method.run), sending the events to a channel until its
+    /// receiver is dropped.
+    pub fn run_with(&mut self, events: Sender<Event>) {
+        self.run(|event| events.send(event).is_ok())
+    }
+}
+
+/// Changes from one observation of a location to the next.
+fn diff(last: &WeatherInfo, info: &WeatherInfo, thresholds: &[(Field, f64)]) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    let condition = |info: &WeatherInfo| info.weather.as_ref().and_then(|w| w.first()).cloned();
+    if let (Some(from), Some(to)) = (condition(last), condition(info)) {
+        if from.id != to.id {
+            changes.push(Change::ConditionChanged {
+                from: Box::new(from),
+                to: Box::new(to),
+            });
+        }
+    }
+
+    for &(field, threshold) in thresholds {
+        if let (Some(from), Some(to)) = (field.value(last), field.value(info)) {
+            let above = |value: f64| value >= threshold;
+            if above(from) != above(to) {
+                changes.push(Change::ThresholdCrossed {
+                    field,
+                    threshold,
+                    from,
+                    to,
+                });
+            }
+        }
+    }
+
+    for &kind in &[Precipitation::Rain, Precipitation::Snow] {
+        match (kind.is_falling(last), kind.is_falling(info)) {
+            (false, true) => changes.push(Change::PrecipitationStarted(kind)),
+            (true, false) => changes.push(Change::PrecipitationStopped(kind)),
+            _ => (),
+        }
+    }
+
+    changes
+}
@@ -0,0 +1,98 @@
+extern crate owm;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use self::owm::{Error, ErrorKind, FormatResponse, Location, Units, WeatherHub};
+use mock::{MockRequest, MockServer};
+
+/// Answers with the weather of London, reporting the queried ID as the city ID.
+/// Unknown cities (negative IDs) get a 404.
+fn weather(req: &MockRequest) -> (u16, String) {
+    let id = req.param("id").unwrap();
+
+    if id.starts_with('-') {
+        (404, include_str!("../fixtures/error_not_found.json").to_string())
+    } else {
+        (200, include_str!("../fixtures/weather_clear.json").replace("2643743", id))
+    }
+}
+
+#[test]
+fn hub_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<WeatherHub>();
+}
+
+#[test]
+fn results_follow_locations() {
+    let server = MockServer::new(weather);
+    let hub = server.hub();
+
+    let locations: Vec<Location> = (1..21).map(Location::id).collect();
+    let results = hub.batch().concurrency(3).current(&locations);
+
+    assert_eq!(20, results.len());
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(Some(i as i64 + 1), result.as_ref().unwrap().1.id);
+    }
+    assert_eq!(20, server.requests().len());
+    assert!(hub.batch().current(&[]).is_empty());
+}
+
+#[test]
+fn errors_are_reported_per_location() {
+    let server = MockServer::new(weather);
+    let hub = server.hub();
+
+    let locations = vec![Location::id(1), Location::id(-1), Location::id(3)];
+    let results = hub.batch().units(Units::Metric).current(&locations);
+
+    assert!(results[0].is_ok());
+    match results[1] {
+        Err(ref err @ Error::BadRequest(_)) => assert_eq!(ErrorKind::NotFound, err.kind()),
+        ref res => panic!("unexpected result: {:?}", res),
+    }
+    assert!(results[2].is_ok());
+
+    let requests = server.requests();
+    assert!(requests.iter().all(|r| r.param("units") == Some("metric")));
+}
+
+#[test]
+fn concurrency_is_bounded() {
+    let (running, peak) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let (r, p) = (running.clone(), peak.clone());
+
+    let server = MockServer::new(move |req: &MockRequest| {
+        let now = r.fetch_add(1, Ordering::SeqCst) + 1;
+        p.fetch_max(now, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(100));
+        r.fetch_sub(1, Ordering::SeqCst);
+        weather(req)
+    });
+    let hub = server.hub();
+
+    let locations: Vec<Location> = (1..9).map(Location::id).collect();
+    let start = Instant::now();
+    let results = hub.batch().concurrency(2).current(&locations);
+
+    assert!(results.iter().all(|r| r.is_ok()));
+    assert_eq!(2, peak.load(Ordering::SeqCst));
+    assert!(start.elapsed() >= Duration::from_millis(400));
+    assert!(start.elapsed() < Duration::from_millis(800));
+}
+
+#[test]
+fn batch_respects_rate_limit() {
+    let server = MockServer::new(weather);
+    let hub = server.hub().rate_limit(600);
+
+    let locations: Vec<Location> = (1..6).map(Location::id).collect();
+    let start = Instant::now();
+    let results = hub.batch().concurrency(5).current(&locations);
+
+    assert!(results.iter().all(|r| r.is_ok()));
+    assert!(start.elapsed() >= Duration::from_millis(400));
+}
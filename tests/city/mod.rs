@@ -0,0 +1,229 @@
+extern crate owm;
+
+use self::owm::Error;
+use self::owm::city::{City, CityList};
+use self::owm::geo;
+
+fn cities() -> CityList {
+    CityList::from_path("tests/fixtures/city.list.json").unwrap()
+}
+
+fn ids(cities: Vec<&City>) -> Vec<i32> {
+    cities.iter().map(|city| city.id).collect()
+}
+
+#[test]
+fn load_plain_and_gzip() {
+    let plain = cities();
+    let gzip = CityList::from_path("tests/fixtures/city.list.json.gz").unwrap();
+
+    assert_eq!(38, plain.len());
+    assert_eq!(plain.len(), gzip.len());
+    assert!(plain.iter().zip(gzip.iter()).all(|(a, b)| a == b));
+}
+
+#[test]
+fn load_errors() {
+    match CityList::from_path("tests/fixtures/missing.json") {
+        Err(Error::IoError(_)) => (),
+        res => panic!("unexpected result: {:?}", res.map(|list| list.len())),
+    }
+    match CityList::from_reader(&b"[{\"id\": \"x\"}]"[..]) {
+        Err(Error::JsonDecodeError(ref body, _)) => assert!(body.is_empty()),
+        res => panic!("unexpected result: {:?}", res.map(|list| list.len())),
+    }
+    match CityList::from_reader(&b"\x1f\x8b\x08\x00garbage"[..]) {
+        Err(Error::IoError(_)) => (),
+        res => panic!("unexpected result: {:?}", res.map(|list| list.len())),
+    }
+}
+
+#[test]
+fn lookup_by_id() {
+    let cities = cities();
+
+    let pisa = cities.get(6542122).unwrap();
+    assert_eq!("Pisa", pisa.name);
+    assert_eq!("IT", pisa.country);
+    assert_eq!(None, pisa.state);
+    assert_eq!(43.7085, pisa.lat);
+
+    assert_eq!(Some("KY".to_string()), cities.get(4298960).unwrap().state);
+    assert!(cities.get(1).is_none());
+}
+
+#[test]
+fn search_by_name() {
+    let cities = cities();
+
+    assert_eq!(vec![2643743, 6058560, 4298960, 4517009],
+               ids(cities.search("london", None)));
+    assert_eq!(vec![6058560], ids(cities.search("LONDON", Some("ca"))));
+    assert_eq!(vec![3448439], ids(cities.search("sao paulo", None)));
+    assert_eq!(vec![2657896], ids(cities.search("  Zurich ", Some("CH"))));
+    assert_eq!(vec![3081368], ids(cities.search("wroclaw", None)));
+    assert!(cities.search("Atlantis", None).is_empty());
+}
+
+#[test]
+fn autocomplete() {
+    let cities = cities();
+
+    assert_eq!(vec![2643743, 6058560, 4298960, 4517009, 3458449],
+               ids(cities.autocomplete("lon", None, 10)));
+    assert_eq!(vec![3458449], ids(cities.autocomplete("Lon", Some("BR"), 10)));
+    assert_eq!(2, cities.autocomplete("lon", None, 2).len());
+    assert_eq!(vec![3173435], ids(cities.autocomplete("LIV", None, 10)));
+    assert_eq!(vec![3448439], ids(cities.autocomplete("são", None, 10)));
+    assert!(cities.autocomplete("xyz", None, 10).is_empty());
+}
+
+#[test]
+fn great_circle_distance() {
+    // Pisa to Firenze
+    let d = geo::distance(43.7085, 10.4036, 43.77925, 11.24626);
+    assert!((d - 68.1).abs() < 0.5, "got {}", d);
+
+    // Across the antimeridian
+    let d = geo::distance(0.0, 179.5, 0.0, -179.5);
+    assert!((d - 111.2).abs() < 0.5, "got {}", d);
+}
+
+#[test]
+fn nearest_cities() {
+    let cities = cities();
+    let index = cities.spatial_index();
+
+    let found = index.nearest(43.71, 10.40, 3);
+    assert_eq!(vec![6542122, 3173435, 3170647],
+               found.iter().map(|&(city, _)| city.id).collect::<Vec<_>>());
+    assert!(found[0].1 < 1.0);
+    assert!(found[1].1 <= found[2].1);
+
+    assert_eq!(cities.len(), index.nearest(0.0, 0.0, 100).len());
+    assert!(index.nearest(0.0, 0.0, 0).is_empty());
+}
+
+#[test]
+fn nearest_across_antimeridian() {
+    let cities = cities();
+    let index = cities.spatial_index();
+
+    let found = index.nearest(-18.5, -179.9, 2);
+    assert_eq!(2198148, found[0].0.id); // Suva
+    assert_eq!(4032402, found[1].0.id); // Nuku'alofa
+}
+
+#[test]
+fn cities_within_radius() {
+    let cities = cities();
+    let index = cities.spatial_index();
+
+    let found = index.within(51.5, -0.1, 10.0);
+    assert_eq!(vec![2643741, 2643743],
+               found.iter().map(|&(city, _)| city.id).collect::<Vec<_>>());
+    assert!(found.iter().all(|&(_, dist)| dist <= 10.0));
+
+    assert!(index.within(0.0, -30.0, 100.0).is_empty());
+    assert_eq!(cities.len(), index.within(0.0, 0.0, 30000.0).len());
+}
+
+#[test]
+fn spatial_index_matches_brute_force() {
+    let cities = cities();
+    let index = cities.spatial_index();
+    let mut seed = 42u64;
+    let mut next = || {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 11) as f64 / (1u64 << 53) as f64
+    };
+
+    for _ in 0..200 {
+        let (lat, lon) = (next() * 180.0 - 90.0, next() * 360.0 - 180.0);
+
+        let mut expected: Vec<(i32, f64)> = cities.iter()
+            .map(|c| (c.id, geo::distance(lat, lon, c.lat, c.lon)))
+            .collect();
+        expected.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        let found: Vec<i32> = index.nearest(lat, lon, 5).iter().map(|&(c, _)| c.id).collect();
+        let expected_ids: Vec<i32> = expected.iter().take(5).map(|&(id, _)| id).collect();
+        assert_eq!(expected_ids, found);
+
+        let radius = next() * 3000.0;
+        let found = index.within(lat, lon, radius).len();
+        assert_eq!(expected.iter().filter(|&&(_, d)| d <= radius).count(), found);
+    }
+}
+
+fn resolved(cities: &CityList, query: &str) -> Vec<i32> {
+    cities.resolve(query, 10).iter().map(|c| c.city.id).collect()
+}
+
+#[test]
+fn population_from_bulk_file() {
+    let cities = cities();
+
+    assert_eq!(Some(7556900), cities.get(2643743).unwrap().population);
+    assert_eq!(None, cities.get(3173435).unwrap().population);
+}
+
+#[test]
+fn resolve_ranks_candidates() {
+    let cities = cities();
+
+    let found = cities.resolve("London", 10);
+    assert_eq!(vec![2643743, 6058560, 4517009, 4298960],
+               found.iter().map(|c| c.city.id).take(4).collect::<Vec<_>>());
+    assert!(found.windows(2).all(|w| w[0].score >= w[1].score));
+    assert!(found.iter().all(|c| c.score > 0.0 && c.score <= 1.0));
+
+    assert_eq!(6058560, resolved(&cities, "london, ca")[0]);
+    assert_eq!(4298960, resolved(&cities, "London, KY, US")[0]);
+    assert_eq!(4717560, resolved(&cities, "Paris, TX")[0]);
+    assert_eq!(2988507, resolved(&cities, "paris, fr")[0]);
+
+    // Only country codes are matched, a full name scores like an unknown code
+    let scores = |query| -> Vec<(i32, f64)> {
+        cities.resolve(query, 10).iter().map(|c| (c.city.id, c.score)).collect()
+    };
+    assert_eq!(scores("Paris, ZZ"), scores("Paris, France"));
+    assert!(scores("Paris, France")[0].1 < scores("Paris, FR")[0].1);
+    assert!(resolved(&cities, "").is_empty());
+    assert!(resolved(&cities, "Atlantis").is_empty());
+}
+
+#[test]
+fn resolve_tolerates_typos() {
+    let cities = cities();
+
+    assert_eq!(2643743, resolved(&cities, "Londn, UK")[0]);
+    assert_eq!(3176959, resolved(&cities, "firenza")[0]);
+    assert_eq!(3448439, resolved(&cities, "Sao Paolo, BR")[0]);
+    assert_eq!(3081368, resolved(&cities, "Wroclaw")[0]);
+}
+
+#[test]
+fn resolve_strict() {
+    let cities = cities();
+
+    assert_eq!(6542122, cities.resolve_strict("Pisa").unwrap().id);
+    assert_eq!(2643743, cities.resolve_strict("London, GB").unwrap().id);
+    assert_eq!(4163971, cities.resolve_strict("Melbourne, FL, US").unwrap().id);
+
+    match cities.resolve_strict("London") {
+        Err(Error::AmbiguousLocation(candidates)) => {
+            assert_eq!(vec![2643743, 6058560, 4517009, 4298960],
+                       candidates.iter().map(|c| c.city.id).collect::<Vec<_>>());
+        }
+        res => panic!("unexpected result: {:?}", res),
+    }
+    match cities.resolve_strict("Melbourne") {
+        Err(Error::AmbiguousLocation(candidates)) => assert_eq!(2, candidates.len()),
+        res => panic!("unexpected result: {:?}", res),
+    }
+    match cities.resolve_strict("Atlantis") {
+        Err(Error::LocationNotFound(query)) => assert_eq!("Atlantis", query),
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
@@ -0,0 +1,197 @@
+extern crate serde_json;
+
+use std::process::{Command, Output};
+use mock::{MockRequest, MockServer};
+
+fn respond(req: &MockRequest) -> (u16, String) {
+    if req.param("appid") != Some("test-key") {
+        return (401, r#"{"cod":401,"message":"Invalid API key"}"#.to_string());
+    }
+
+    match req.param("id") {
+        Some("404") => (404, include_str!("../fixtures/error_not_found.json").to_string()),
+        Some("500") => (500, "Internal Server Error".to_string()),
+        Some("666") => (200, "{\"main\": ".to_string()),
+        _ if req.path.ends_with("/box/city") => {
+            (200, include_str!("../fixtures/box_city.json").to_string())
+        }
+        _ if req.path.ends_with("/find") => {
+            (200, include_str!("../fixtures/find.json").to_string())
+        }
+        _ => (200, include_str!("../fixtures/weather_clear.json").to_string()),
+    }
+}
+
+fn owm(server: &MockServer, args: &[&str]) -> Output {
+    owm_with_key(server, "test-key", args)
+}
+
+fn owm_with_key(server: &MockServer, key: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_owm"))
+        .args(["--base-url", &server.url(), "--api-key", key, "--output", "json"])
+        .args(args)
+        .env_remove("OWM_API_KEY")
+        .output()
+        .unwrap()
+}
+
+fn stdout_json(output: &Output) -> serde_json::Value {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn query_modes() {
+    let server = MockServer::new(respond);
+
+    let info = stdout_json(&owm(&server, &["name", "London", "-c", "GB", "-u", "metric", "-l", "it"]));
+    assert_eq!("London", info["name"]);
+    stdout_json(&owm(&server, &["id", "2643743"]));
+    stdout_json(&owm(&server, &["zip", "SW1A 1AA", "--country", "GB"]));
+    stdout_json(&owm(&server, &["coords", "51.5", "-0.12"]));
+
+    let agg = stdout_json(&owm(&server, &["bbox", "44", "43", "10", "11", "--zoom", "8"]));
+    assert_eq!(2, agg["cnt"]);
+    let agg = stdout_json(&owm(&server, &["circle", "43.7", "10.4", "-n", "2", "--cluster"]));
+    assert_eq!(2, agg["count"]);
+
+    let requests = server.requests();
+    assert_eq!(6, requests.len());
+    assert_eq!(Some("London,GB"), requests[0].param("q"));
+    assert_eq!(Some("metric"), requests[0].param("units"));
+    assert_eq!(Some("it"), requests[0].param("lang"));
+    assert_eq!(Some("2643743"), requests[1].param("id"));
+    assert_eq!(Some("SW1A 1AA,GB"), requests[2].param("zip"));
+    assert_eq!(Some("-0.12"), requests[3].param("lon"));
+    assert_eq!(Some("10,43,11,44,8"), requests[4].param("bbox"));
+    assert_eq!(Some("yes"), requests[5].param("cluster"));
+}
+
+#[test]
+fn exit_codes() {
+    let server = MockServer::new(respond);
+    let code = |args: &[&str]| owm(&server, args).status.code();
+
+    assert_eq!(Some(2), code(&["coords", "91", "0"]));
+    assert_eq!(Some(2), code(&["id", "not-a-number"]));
+    assert_eq!(Some(3), code(&["id", "500"]));
+    assert_eq!(Some(4), owm_with_key(&server, "wrong", &["id", "1"]).status.code());
+    assert_eq!(Some(5), code(&["id", "404"]));
+    assert_eq!(Some(6), code(&["id", "666"]));
+    assert_eq!(Some(1), code(&["name", "London", "--city-list", "/nonexistent"]));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_owm"))
+        .args(["id", "1"])
+        .env_remove("OWM_API_KEY")
+        .env("XDG_CONFIG_HOME", "/nonexistent")
+        .output()
+        .unwrap();
+    assert_eq!(Some(4), output.status.code());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_owm"))
+        .args(["--base-url", "http://127.0.0.1:1/data", "--api-key", "test-key", "id", "1"])
+        .output()
+        .unwrap();
+    assert_eq!(Some(3), output.status.code());
+}
+
+#[test]
+fn strict_name_resolution() {
+    let server = MockServer::new(respond);
+    let list = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/city.list.json");
+
+    stdout_json(&owm(&server, &["name", "Pisa", "--city-list", list]));
+    assert_eq!(Some("6542122"), server.requests()[0].param("id"));
+
+    let output = owm(&server, &["name", "London", "--city-list", list]);
+    assert_eq!(Some(2), output.status.code());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("did you mean"));
+}
+
+#[test]
+fn status_bar_outputs() {
+    let server = MockServer::new(respond);
+    let status_bar = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_owm"))
+            .args(["--base-url", &server.url(), "--api-key", "test-key"])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let stdout = |args: &[&str]| {
+        let output = status_bar(args);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!("London 283°K ☀\n",
+               stdout(&["-o", "text", "-t", "{city} {temp:.0}°{units} {condition_icon}", "id", "1"]));
+    assert_eq!("Pisa\nPisa\n", stdout(&["-o", "text", "-t", "{city}", "circle", "43.7", "10.4"]));
+
+    let module: serde_json::Value =
+        serde_json::from_str(&stdout(&["-o", "waybar", "--icons", "nerd-font", "id", "1"])).unwrap();
+    assert_eq!("normal", module["class"]);
+    assert_eq!("283 K \u{e30d}", module["text"]);
+
+    assert_eq!(Some(2), status_bar(&["-o", "i3bar", "-t", "{nope}", "id", "1"]).status.code());
+    assert_eq!(3, server.requests().len());
+}
+
+#[test]
+fn influx_output() {
+    let server = MockServer::new(respond);
+    let influx = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_owm"))
+            .args(["--base-url", &server.url(), "--api-key", "test-key", "-o", "influx"])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = influx(&["--fields", "temp,humidity", "--precision", "s", "id", "2643743"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!("weather,city_id=2643743,city=London,country=GB temp=282.55,humidity=100i 1560350645\n",
+               String::from_utf8_lossy(&output.stdout));
+
+    let output = influx(&["bbox", "44", "43", "10", "11"]);
+    assert_eq!(2, String::from_utf8_lossy(&output.stdout).lines().count());
+
+    assert_eq!(Some(2), influx(&["--fields", "temp,nope", "id", "1"]).status.code());
+    assert_eq!(Some(2), influx(&["--precision", "m", "id", "1"]).status.code());
+}
+
+#[test]
+fn configuration_file() {
+    let server = MockServer::new(respond);
+    let path = std::env::temp_dir().join(format!("owm-cli-{}.toml", std::process::id()));
+    std::fs::write(&path,
+                   format!("api_key = \"test-key\"\nbase_url = \"{}\"\nunits = \"metric\"\n\n\
+                            [locations.home]\ntype = \"id\"\nid = 2643743\n",
+                           server.url()))
+        .unwrap();
+    let owm = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_owm"))
+            .args(["--config", path.to_str().unwrap(), "--output", "json"])
+            .args(args)
+            .env_remove("OWM_API_KEY")
+            .env_remove("OWM_UNITS")
+            .output()
+            .unwrap()
+    };
+
+    stdout_json(&owm(&["saved", "home"]));
+    stdout_json(&owm(&["-u", "imperial", "saved", "home"]));
+    assert_eq!(Some(5), owm(&["saved", "gym"]).status.code());
+
+    let requests = server.requests();
+    assert_eq!(Some("2643743"), requests[0].param("id"));
+    assert_eq!(Some("metric"), requests[0].param("units"));
+    assert_eq!(Some("imperial"), requests[1].param("units"));
+
+    std::fs::write(&path, "units = \"kelvin\"\n").unwrap();
+    let output = owm(&["saved", "home"]);
+    assert_eq!(Some(2), output.status.code());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("units: expected"));
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,139 @@
+extern crate hyper;
+extern crate owm;
+
+use std::collections::HashMap;
+use std::time::Duration;
+use self::owm::config::{CacheConfig, Config, DEFAULT_CACHE_CAPACITY};
+use self::owm::{Error, FormatResponse, Location, Units};
+use mock::{MockRequest, MockServer};
+
+const FILE: &str = r#"
+api_key = "file-key"
+units = "metric"
+lang = "it"
+rate_limit = 600
+
+[cache]
+ttl = 60
+
+[locations.home]
+type = "name"
+city = "London"
+country = "GB"
+
+[locations.office]
+type = "coords"
+lat = 43.7085
+lon = 10.4036
+"#;
+
+fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> =
+        vars.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect();
+    move |name| vars.get(name).cloned()
+}
+
+fn invalid(text: &str) -> String {
+    match Config::parse(text) {
+        Err(Error::InvalidConfig(msg)) => msg,
+        other => panic!("{}: {:?}", text, other),
+    }
+}
+
+#[test]
+fn parse_file() {
+    let config = Config::parse(FILE).unwrap();
+
+    assert_eq!(Some("file-key".to_string()), config.api_key);
+    assert_eq!(None, config.base_url);
+    assert_eq!(Some(Units::Metric), config.units);
+    assert_eq!(Some("it".to_string()), config.lang);
+    assert_eq!(Some(600), config.rate_limit);
+    assert_eq!(Some(CacheConfig {
+                   ttl: Duration::from_secs(60),
+                   capacity: DEFAULT_CACHE_CAPACITY,
+               }),
+               config.cache);
+    assert_eq!(&Location::name("London", Some("GB")), config.location("home").unwrap());
+    assert_eq!(&Location::coords(43.7085, 10.4036).unwrap(), config.location("office").unwrap());
+
+    match config.location("gym") {
+        Err(Error::LocationNotFound(msg)) => assert!(msg.contains("gym")),
+        other => panic!("{:?}", other),
+    }
+
+    assert_eq!(Config::default(), Config::parse("").unwrap());
+}
+
+#[test]
+fn validation_errors() {
+    assert!(invalid("units = \"celsius\"").starts_with("units: expected standard"));
+    assert!(invalid("rate_limit = 0").starts_with("rate_limit: expected a positive number"));
+    assert!(invalid("base_url = \"ftp://example.com\"").starts_with("base_url:"));
+    assert!(invalid("api_key = \" \"").starts_with("api_key: empty"));
+    assert!(invalid("[cache]\nttl = -1").starts_with("cache.ttl:"));
+    assert!(invalid("[cache]\nttl = 1\ncapacity = 0").starts_with("cache.capacity:"));
+    assert_eq!("latitude 91 out of range at line 1",
+               invalid("[locations.pole]\ntype = \"coords\"\nlat = 91\nlon = 0"));
+    assert!(invalid("unit = \"metric\"").contains("unknown field `unit`"));
+    assert!(invalid("api_key = \"a\"\nunits = ").ends_with("at line 2"));
+    assert_eq!(None, Config::parse("[cache]\nttl = 0").unwrap().cache);
+
+    match Config::from_path("/nonexistent/config.toml") {
+        Err(Error::IoError(_)) => (),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn environment_overrides_file() {
+    let config = Config::parse(FILE)
+        .unwrap()
+        .with_env(env(&[("OWM_API_KEY", "env-key"),
+                        ("OWM_UNITS", "imperial"),
+                        ("OWM_LANG", ""),
+                        ("OWM_CACHE_TTL", "0")]))
+        .unwrap();
+
+    assert_eq!(Some("env-key".to_string()), config.api_key);
+    assert_eq!(Some(Units::Imperial), config.units);
+    assert_eq!(Some("it".to_string()), config.lang);
+    assert_eq!(Some(600), config.rate_limit);
+    assert_eq!(None, config.cache);
+
+    match Config::default().with_env(env(&[("OWM_RATE_LIMIT", "lots")])) {
+        Err(Error::InvalidConfig(msg)) => assert!(msg.starts_with("OWM_RATE_LIMIT:")),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn hub_from_config() {
+    let server = MockServer::new(|_: &MockRequest| {
+        (200, include_str!("../fixtures/weather_clear.json").to_string())
+    });
+
+    match Config::default().hub(hyper::Client::new()) {
+        Err(Error::InvalidConfig(msg)) => assert!(msg.contains("api_key")),
+        other => panic!("{:?}", other.map(|_| ())),
+    }
+
+    let config = Config::parse(FILE)
+        .unwrap()
+        .with_env(env(&[("OWM_BASE_URL", &server.url())]))
+        .unwrap();
+    let hub = config.hub(hyper::Client::new()).unwrap();
+
+    let (_, first) = hub.current().at(config.location("home").unwrap()).unwrap();
+    let (res, cached) = hub.current().at(config.location("home").unwrap()).unwrap();
+    assert_eq!(first.id, cached.id);
+    assert!(res.status.is_success());
+    hub.current().lang("en").at(config.location("home").unwrap()).unwrap();
+
+    let requests = server.requests();
+    assert_eq!(2, requests.len());
+    assert_eq!(Some("file-key"), requests[0].param("appid"));
+    assert_eq!(Some("metric"), requests[0].param("units"));
+    assert_eq!(Some("it"), requests[0].param("lang"));
+    assert_eq!(Some("en"), requests[1].param("lang"));
+}
@@ -0,0 +1,142 @@
+extern crate owm;
+extern crate serde_json;
+
+use self::owm::Error;
+use self::owm::data::{WeatherInfo, WeatherAggregate, WeatherBoxAggregate, ErrorResponse,
+                      DecodeMode, decode};
+
+#[test]
+fn decode_clear_sky() {
+    let info: WeatherInfo = serde_json::from_str(include_str!("../fixtures/weather_clear.json"))
+        .unwrap();
+
+    let main = info.main.clone().unwrap();
+    assert_eq!(Some(281.86), main.feels_like);
+    assert_eq!(Some(1023.0), main.pressure);
+    assert_eq!(Some(100), main.humidity);
+    assert_eq!(Some(10000), info.visibility);
+    assert_eq!(Some(3600), info.timezone);
+    assert_eq!(Some(350.0), info.wind.clone().unwrap().deg);
+    assert_eq!(Some("GB".to_string()), info.sys.clone().unwrap().country);
+    assert_eq!(Some(200), info.cod);
+    assert!(info.rain.is_none());
+}
+
+#[test]
+fn decode_fractional_rain() {
+    let info: WeatherInfo = serde_json::from_str(include_str!("../fixtures/weather_rain.json"))
+        .unwrap();
+
+    let rain = info.rain.clone().unwrap();
+    assert_eq!(Some(0.25), rain.one_hour);
+    assert_eq!(None, rain.three_hours);
+    assert_eq!(Some(1.18), info.wind.clone().unwrap().gust);
+    assert_eq!(Some(933.0), info.main.clone().unwrap().grnd_level);
+}
+
+#[test]
+fn decode_snow_and_fractional_wind() {
+    let info: WeatherInfo = serde_json::from_str(include_str!("../fixtures/weather_snow.json"))
+        .unwrap();
+
+    let snow = info.snow.clone().unwrap();
+    assert_eq!(Some(0.31), snow.one_hour);
+    assert_eq!(Some(0.87), snow.three_hours);
+    assert_eq!(Some(247.5), info.wind.clone().unwrap().deg);
+    assert_eq!(2, info.weather.clone().unwrap().len());
+    assert_eq!(Some(2900), info.visibility);
+}
+
+#[test]
+fn decode_circle_aggregate() {
+    let agg: WeatherAggregate = serde_json::from_str(include_str!("../fixtures/find.json"))
+        .unwrap();
+
+    assert_eq!(Some(200), agg.cod);
+    assert_eq!(Some(2), agg.count);
+
+    let list = agg.list.unwrap();
+    assert_eq!(Some(1017.28), list[0].main.clone().unwrap().pressure);
+    assert!(list[0].rain.is_none());
+    assert_eq!(Some(0.12), list[1].rain.clone().unwrap().one_hour);
+}
+
+#[test]
+fn decode_box_aggregate() {
+    let agg: WeatherBoxAggregate = serde_json::from_str(include_str!("../fixtures/box_city.json"))
+        .unwrap();
+
+    assert_eq!(Some(200), agg.cod);
+    assert_eq!(Some(2), agg.cnt);
+
+    let list = agg.list.unwrap();
+    assert_eq!(Some(10.4036), list[0].coord.clone().unwrap().lon);
+    assert_eq!(Some(43.55), list[1].coord.clone().unwrap().lat);
+    assert_eq!(Some(20), list[1].clouds.clone().unwrap().all);
+}
+
+#[test]
+fn decode_error_response() {
+    let err: ErrorResponse = serde_json::from_str(include_str!("../fixtures/error_not_found.json"))
+        .unwrap();
+
+    assert_eq!(Some(404), err.cod);
+    assert_eq!(Some("city not found".to_string()), err.message);
+}
+
+#[test]
+fn preserve_unknown_fields() {
+    let info: WeatherInfo = serde_json::from_str(include_str!("../fixtures/box_city.json"))
+        .map(|agg: WeatherBoxAggregate| agg.list.unwrap()[0].clone())
+        .unwrap();
+    assert!(info.extra.is_empty());
+
+    let info: WeatherInfo = decode(include_str!("../fixtures/weather_malformed.json"),
+                                   DecodeMode::Lenient)
+        .unwrap();
+    assert_eq!(Some(&serde_json::Value::from(3.1)), info.extra.get("uvi"));
+    assert_eq!(Some(&serde_json::Value::from(-1.2)),
+               info.main.unwrap().extra.get("temp_kf"));
+
+    let json = serde_json::to_value(&info.extra).unwrap();
+    assert_eq!(Some(3.1), json["uvi"].as_f64());
+}
+
+#[test]
+fn strict_decode_rejects_malformed_fields() {
+    match decode::<WeatherInfo>(include_str!("../fixtures/weather_malformed.json"),
+                                DecodeMode::Strict) {
+        Err(Error::JsonDecodeError(_, _)) => (),
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
+
+#[test]
+fn lenient_decode_discards_malformed_fields() {
+    let info: WeatherInfo = decode(include_str!("../fixtures/weather_malformed.json"),
+                                   DecodeMode::Lenient)
+        .unwrap();
+
+    assert_eq!(None, info.visibility);
+    assert_eq!(None, info.main.clone().unwrap().humidity);
+    assert_eq!(Some(1017.0), info.main.clone().unwrap().pressure);
+    assert_eq!(Some("Pisa".to_string()), info.name);
+
+    let paths: Vec<&str> = info.warnings.iter().map(|w| w.path.as_str()).collect();
+    assert_eq!(vec!["main.humidity", "visibility"], paths);
+}
+
+#[test]
+fn lenient_decode_in_aggregates() {
+    let body = include_str!("../fixtures/find.json").replace("\"deg\":60", "\"deg\":\"NE\"");
+
+    let agg: WeatherAggregate = decode(&body, DecodeMode::Lenient).unwrap();
+    let list = agg.list.unwrap();
+
+    assert_eq!(2, list.len());
+    assert_eq!(None, list[1].wind.clone().unwrap().deg);
+    assert_eq!(Some(2.06), list[1].wind.clone().unwrap().speed);
+
+    let paths: Vec<&str> = agg.warnings.iter().map(|w| w.path.as_str()).collect();
+    assert_eq!(vec!["list[0].wind.deg", "list[1].wind.deg"], paths);
+}
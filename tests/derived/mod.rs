@@ -0,0 +1,97 @@
+extern crate owm;
+extern crate serde_json;
+
+use self::owm::Units;
+use self::owm::data::{Main, WeatherInfo, Wind};
+use self::owm::derived;
+
+fn assert_close(expected: f32, actual: Option<f32>) {
+    let actual = actual.unwrap();
+    assert!((expected - actual).abs() < 0.05,
+            "expected {}, got {}",
+            expected,
+            actual);
+}
+
+fn observation(temp: f32, humidity: i32, speed: f32) -> WeatherInfo {
+    WeatherInfo {
+        main: Some(Main {
+            temp: Some(temp),
+            humidity: Some(humidity),
+            ..Default::default()
+        }),
+        wind: Some(Wind {
+            speed: Some(speed),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn formulas() {
+    assert_close(23.93, Some(derived::dew_point(30.0, 70.0)));
+    assert_close(35.04, Some(derived::heat_index(30.0, 70.0)));
+    assert_close(-20.30, Some(derived::wind_chill(-10.0, 10.0)));
+    assert_close(41.20, Some(derived::humidex(30.0, 70.0)));
+    assert_close(34.37, Some(derived::apparent_temperature(30.0, 70.0, 2.0)));
+    assert_close(21.25, Some(derived::absolute_humidity(30.0, 70.0)));
+}
+
+#[test]
+fn wind_chill_outside_validity() {
+    assert_close(15.0, Some(derived::wind_chill(15.0, 10.0)));
+    assert_close(-5.0, Some(derived::wind_chill(-5.0, 1.0)));
+}
+
+#[test]
+fn metric_units() {
+    let info = observation(30.0, 70, 2.0);
+    let derived = info.derived(Units::Metric);
+
+    assert_close(23.93, derived.dew_point());
+    assert_close(35.04, derived.heat_index());
+    assert_close(30.0, derived.wind_chill());
+    assert_close(41.20, derived.humidex());
+    assert_close(34.37, derived.apparent_temperature());
+    assert_close(21.25, derived.absolute_humidity());
+}
+
+#[test]
+fn imperial_units() {
+    let info = observation(86.0, 70, 2.0 / 0.44704);
+    let derived = info.derived(Units::Imperial);
+
+    assert_close(75.07, derived.dew_point());
+    assert_close(95.07, derived.heat_index());
+    assert_close(93.86, derived.apparent_temperature());
+    assert_close(21.25, derived.absolute_humidity());
+
+    let info = observation(14.0, 50, 10.0 / 0.44704);
+    assert_close(-4.55, info.derived(Units::Imperial).wind_chill());
+}
+
+#[test]
+fn standard_units() {
+    let info: WeatherInfo = serde_json::from_str(include_str!("../fixtures/weather_rain.json"))
+        .unwrap();
+    let derived = info.derived(Units::Standard);
+
+    assert_close(291.18, derived.dew_point());
+    assert_close(300.85, derived.apparent_temperature());
+    assert_close(15.01, derived.absolute_humidity());
+}
+
+#[test]
+fn missing_inputs() {
+    let mut info = observation(30.0, 70, 2.0);
+    info.wind = None;
+
+    let derived = info.derived(Units::Metric);
+    assert!(derived.dew_point().is_some());
+    assert_eq!(None, derived.wind_chill());
+    assert_eq!(None, derived.apparent_temperature());
+
+    let info = WeatherInfo::default();
+    assert_eq!(None, info.derived(Units::Metric).dew_point());
+}
@@ -0,0 +1,90 @@
+extern crate hyper;
+
+use std::io::Read;
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use mock::{MockRequest, MockServer};
+
+/// Kills the exporter when the test ends, even on failure.
+struct Exporter(Child);
+
+impl Drop for Exporter {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn respond(req: &MockRequest) -> (u16, String) {
+    match req.param("q") {
+        Some(_) => (404, include_str!("../fixtures/error_not_found.json").to_string()),
+        None => (200, include_str!("../fixtures/weather_clear.json").to_string()),
+    }
+}
+
+/// Body and status of a GET request, if the exporter is up.
+fn get(url: &str) -> Option<(u16, String)> {
+    let mut res = hyper::Client::new().get(url).send().ok()?;
+    let mut body = String::new();
+    res.read_to_string(&mut body).ok()?;
+    Some((res.status.to_u16(), body))
+}
+
+#[test]
+fn serves_metrics() {
+    let server = MockServer::new(respond);
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let path = std::env::temp_dir().join(format!("owm-exporter-{}.toml", std::process::id()));
+    std::fs::write(&path,
+                   format!("api_key = \"test-key\"\nbase_url = \"{}\"\n\n[cache]\nttl = 60\n\n\
+                            [locations.home]\ntype = \"id\"\nid = 2643743\n\n\
+                            [locations.nowhere]\ntype = \"name\"\ncity = \"Nowhere\"\n",
+                           server.url()))
+        .unwrap();
+
+    let _exporter = Exporter(Command::new(env!("CARGO_BIN_EXE_owm-exporter"))
+        .args(["--config", path.to_str().unwrap()])
+        .args(["--listen", &format!("127.0.0.1:{}", port), "--interval", "1"])
+        .env_remove("OWM_API_KEY")
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap());
+
+    // Wait for a second poll, answered from the cache
+    let url = format!("http://127.0.0.1:{}", port);
+    let deadline = Instant::now() + Duration::from_secs(20);
+    let metrics = loop {
+        match get(&format!("{}/metrics", url)) {
+            Some((200, body)) if body.contains("owm_cache_hits_total 1") => break body,
+            _ if Instant::now() > deadline => panic!("no metrics served"),
+            _ => thread::sleep(Duration::from_millis(100)),
+        }
+    };
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(metrics.contains("owm_temperature_celsius{location=\"home\",city_id=\"2643743\",city=\"London\"} 282.55\n"));
+    assert!(!metrics.contains("location=\"nowhere\",city_id"));
+    assert!(metrics.contains("owm_location_up{location=\"home\"} 1\n"));
+    assert!(metrics.contains("owm_location_up{location=\"nowhere\"} 0\n"));
+    assert!(metrics.contains("owm_api_errors_total{kind=\"not_found\"} "));
+    assert_eq!(Some("metric"), server.requests()[0].param("units"));
+
+    assert_eq!(Some(404), get(&format!("{}/other", url)).map(|(status, _)| status));
+}
+
+#[test]
+fn requires_locations() {
+    let path = std::env::temp_dir().join(format!("owm-exporter-empty-{}.toml", std::process::id()));
+    std::fs::write(&path, "api_key = \"test-key\"\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_owm-exporter"))
+        .args(["--config", path.to_str().unwrap()])
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(Some(2), output.status.code());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no locations"));
+}
@@ -0,0 +1,96 @@
+extern crate owm;
+extern crate serde_json;
+
+use self::owm::data::{WeatherAggregate, WeatherBoxAggregate, WeatherInfo};
+use self::owm::format::{Format, Render, CSV_COLUMNS};
+
+fn find() -> WeatherAggregate {
+    serde_json::from_str(include_str!("../fixtures/find.json")).unwrap()
+}
+
+#[test]
+fn parse_format_names() {
+    assert_eq!(Ok(Format::Table), "table".parse());
+    assert_eq!(Ok(Format::Ndjson), "ndjson".parse());
+    assert!("xml".parse::<Format>().is_err());
+}
+
+#[test]
+fn table() {
+    let table = find().render(Format::Table);
+    let lines: Vec<&str> = table.lines().collect();
+
+    assert_eq!(4, lines.len());
+    assert!(lines[0].starts_with("City  Country  Temp  Feels  Humidity  Pressure"));
+    assert!(lines[1].starts_with("----  -------"));
+    assert!(lines[2].starts_with("Pisa  IT       12.4   11.6       76%      1017  2.1 ENE"));
+    assert!(lines[2].ends_with("clear sky"));
+    assert!(lines.iter().all(|line| !line.ends_with(' ')));
+
+    let info = WeatherInfo::default();
+    let lines: Vec<String> = info.render(Format::Table).lines().map(str::to_string).collect();
+    assert_eq!("-     -           -      -         -         -     -       -  -", lines[2]);
+}
+
+#[test]
+fn json() {
+    let info: WeatherInfo =
+        serde_json::from_str(include_str!("../fixtures/weather_rain.json")).unwrap();
+    let text = info.render(Format::Json);
+
+    assert!(text.starts_with("{\n  "));
+    assert!(text.contains("\"1h\": 0.25"));
+    assert_eq!(serde_json::to_value(&info).unwrap()["id"],
+               serde_json::from_str::<serde_json::Value>(&text).unwrap()["id"]);
+
+    let agg: serde_json::Value = serde_json::from_str(&find().render(Format::Json)).unwrap();
+    assert_eq!(2, agg["count"]);
+}
+
+#[test]
+fn ndjson() {
+    let text = find().render(Format::Ndjson);
+    let lines: Vec<serde_json::Value> =
+        text.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+    assert_eq!(2, lines.len());
+    assert_eq!(6542122, lines[0]["id"]);
+    assert_eq!(10.4036, lines[0]["coord"]["lon"]);
+    assert_eq!("", WeatherBoxAggregate::default().render(Format::Ndjson));
+}
+
+#[test]
+fn csv() {
+    let text = find().render(Format::Csv);
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(3, lines.len());
+    assert_eq!(CSV_COLUMNS.join(","), lines[0]);
+    assert!(lines[1].starts_with("6542122,Pisa,IT,43.7085,10.4036,1697630400,,800,Clear,clear sky,01n,12.38,"));
+    assert!(lines.iter().all(|line| line.split(',').count() == CSV_COLUMNS.len()));
+
+    // Same columns whatever the fields present
+    let empty = WeatherInfo::default().render(Format::Csv);
+    assert_eq!(format!("{}\n{}\n", CSV_COLUMNS.join(","), ",".repeat(CSV_COLUMNS.len() - 1)),
+               empty);
+
+    // Rows only, to append to an existing file
+    assert_eq!(lines[1..].join("\n") + "\n", find().render(Format::CsvRows));
+    assert_eq!(Ok(Format::CsvRows), "csv-rows".parse());
+}
+
+#[test]
+fn csv_quoting() {
+    let mut info = WeatherInfo {
+        name: Some("Washington, D.C.".to_string()),
+        ..WeatherInfo::default()
+    };
+
+    let text = info.render(Format::Csv);
+    let row = text.lines().nth(1).unwrap();
+    assert!(row.starts_with(",\"Washington, D.C.\","));
+
+    info.name = Some("The \"Big\" Apple".to_string());
+    let text = info.render(Format::Csv);
+    assert!(text.contains(",\"The \"\"Big\"\" Apple\","));
+}
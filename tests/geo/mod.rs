@@ -0,0 +1,144 @@
+extern crate hyper;
+extern crate owm;
+extern crate serde_json;
+
+use self::owm::{BoundingBox, Error, LatLon, WeatherHub};
+use self::owm::data::WeatherBoxAggregate;
+
+fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+    assert!((expected - actual).abs() <= tolerance,
+            "expected {}, got {}",
+            expected,
+            actual);
+}
+
+#[test]
+fn latlon_validation() {
+    let pos = LatLon::new(43.71, 10.41).unwrap();
+    assert_eq!(43.71, pos.lat());
+    assert_eq!(10.41, pos.lon());
+
+    assert_close(-170.0, LatLon::new(0.0, 190.0).unwrap().lon(), 1e-9);
+    assert_close(-180.0, LatLon::new(0.0, 180.0).unwrap().lon(), 1e-9);
+    assert_close(90.0, LatLon::new(0.0, -270.0).unwrap().lon(), 1e-9);
+
+    for &(lat, lon) in &[(90.1, 0.0), (-91.0, 0.0), (f64::NAN, 0.0), (0.0, f64::INFINITY)] {
+        match LatLon::new(lat, lon) {
+            Err(Error::InvalidCoordinates(_)) => (),
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+}
+
+#[test]
+fn bbox_validation() {
+    let bbox = BoundingBox::new(43.73, 43.7, 10.38, 10.42).unwrap();
+    assert!(!bbox.crosses_antimeridian());
+    assert_eq!(vec![bbox], bbox.split());
+
+    let bbox = BoundingBox::new(10.0, 0.0, 170.0, 180.0).unwrap();
+    assert_eq!(180.0, bbox.right);
+    assert!(!bbox.crosses_antimeridian());
+
+    let bbox = BoundingBox::new(10.0, 0.0, 0.0, 400.0).unwrap();
+    assert_eq!((-180.0, 180.0), (bbox.left, bbox.right));
+
+    assert!(BoundingBox::new(0.0, 10.0, 0.0, 10.0).is_err());
+    assert!(BoundingBox::new(95.0, 10.0, 0.0, 10.0).is_err());
+}
+
+#[test]
+fn bbox_across_antimeridian() {
+    let bbox = BoundingBox::new(-10.0, -25.0, 175.0, 190.0).unwrap();
+    assert!(bbox.crosses_antimeridian());
+    assert_close(-170.0, bbox.right, 1e-9);
+    assert_close(15.0, bbox.width(), 1e-9);
+
+    let parts = bbox.split();
+    assert_eq!(2, parts.len());
+    assert_eq!((175.0, 180.0), (parts[0].left, parts[0].right));
+    assert_close(-180.0, parts[1].left, 1e-9);
+    assert_close(-170.0, parts[1].right, 1e-9);
+
+    let center = bbox.center();
+    assert_close(-17.5, center.lat(), 1e-9);
+    assert_close(-177.5, center.lon(), 1e-9);
+
+    assert!(bbox.contains(&LatLon::new(-18.14, 178.44).unwrap())); // Suva
+    assert!(bbox.contains(&LatLon::new(-21.14, -175.2).unwrap())); // Nuku'alofa
+    assert!(!bbox.contains(&LatLon::new(-21.14, 0.0).unwrap()));
+    assert!(!bbox.contains(&LatLon::new(-30.0, 179.0).unwrap()));
+}
+
+#[test]
+fn bbox_area() {
+    let one_degree = BoundingBox::new(0.5, -0.5, 0.0, 1.0).unwrap();
+    assert_close(12364.0, one_degree.area(), 10.0);
+
+    let crossing = BoundingBox::new(0.5, -0.5, 179.5, -179.5).unwrap();
+    assert_close(one_degree.area(), crossing.area(), 1e-6);
+
+    let world = BoundingBox::new(90.0, -90.0, -180.0, 180.0).unwrap();
+    assert_close(510065622.0, world.area(), 1000.0);
+}
+
+#[test]
+fn bbox_expansion() {
+    let bbox = BoundingBox::new(1.0, -1.0, -1.0, 1.0).unwrap().expand(111.195);
+    assert_close(2.0, bbox.top, 1e-3);
+    assert_close(-2.0, bbox.bottom, 1e-3);
+    assert_close(-2.0, bbox.left, 2e-3);
+    assert_close(2.0, bbox.right, 2e-3);
+
+    let suva = LatLon::new(-18.14, 178.44).unwrap();
+    let around = BoundingBox::around(&suva, 500.0);
+    assert!(around.crosses_antimeridian());
+    assert!(around.contains(&LatLon::new(-18.14, -178.0).unwrap()));
+    assert!(around.contains(&suva));
+
+    let polar = BoundingBox::new(85.0, 80.0, 0.0, 10.0).unwrap().expand(1000.0);
+    assert_eq!(90.0, polar.top);
+    assert_eq!((-180.0, 180.0), (polar.left, polar.right));
+}
+
+#[test]
+fn merge_box_results() {
+    let agg: WeatherBoxAggregate = serde_json::from_str(include_str!("../fixtures/box_city.json"))
+        .unwrap();
+
+    let mut merged = agg.clone();
+    merged.merge(agg.clone());
+    assert_eq!(Some(2), merged.cnt);
+    assert_eq!(2, merged.list.clone().unwrap().len());
+
+    let mut other = agg.clone();
+    other.list.as_mut().unwrap()[0].id = Some(1);
+    merged.merge(other);
+    assert_eq!(Some(3), merged.cnt);
+    assert_close(0.0089 * 3.0, merged.calctime.unwrap() as f64, 1e-6);
+}
+
+#[test]
+fn reject_invalid_coordinates() {
+    let hub = WeatherHub::new(hyper::Client::new(), "unused");
+
+    match hub.current().by_coords(95.0, 10.0) {
+        Err(Error::InvalidCoordinates(_)) => (),
+        res => panic!("unexpected result: {:?}", res.map(|(_, info)| info)),
+    }
+    match hub.current().by_circle(43.7, f32::NAN, 10, false) {
+        Err(Error::InvalidCoordinates(_)) => (),
+        res => panic!("unexpected result: {:?}", res.map(|(_, info)| info)),
+    }
+
+    let bbox = BoundingBox {
+        top: 0.0,
+        bottom: 10.0,
+        left: 0.0,
+        right: 10.0,
+    };
+    match hub.current().by_bounds(&bbox, 10, false) {
+        Err(Error::InvalidCoordinates(_)) => (),
+        res => panic!("unexpected result: {:?}", res.map(|(_, info)| info)),
+    }
+}
@@ -0,0 +1,142 @@
+extern crate owm;
+extern crate serde_json;
+
+use self::owm::Error;
+use self::owm::data::{WeatherAggregate, WeatherBoxAggregate, WeatherInfo};
+use self::owm::geojson::{FromGeoJson, ToGeoJson};
+
+#[test]
+fn export_aggregate() {
+    let agg: WeatherBoxAggregate =
+        serde_json::from_str(include_str!("../fixtures/box_city.json")).unwrap();
+    let geojson = agg.to_geojson();
+
+    assert_eq!("FeatureCollection", geojson["type"]);
+    assert_eq!(2, geojson["features"].as_array().unwrap().len());
+
+    let feature = &geojson["features"][1];
+    assert_eq!("Feature", feature["type"]);
+    assert_eq!(json!({"type": "Point", "coordinates": [10.3167, 43.55]}),
+               feature["geometry"]);
+    assert_eq!(feature["properties"]["id"], feature["id"]);
+    assert_eq!(Some(20), feature["properties"]["clouds.all"].as_i64());
+    assert!(feature["properties"]["weather.0.description"].is_string());
+    assert!(feature["properties"].get("coord").is_none());
+    assert!(feature["properties"].get("rain").is_none());
+}
+
+#[test]
+fn export_single_observation() {
+    let info: WeatherInfo =
+        serde_json::from_str(include_str!("../fixtures/weather_snow.json")).unwrap();
+    let geojson = info.to_geojson();
+
+    let features = geojson["features"].as_array().unwrap();
+    assert_eq!(1, features.len());
+
+    let properties = &features[0]["properties"];
+    assert_eq!(Some(0.31), properties["snow.1h"].as_f64());
+    assert_eq!(Some(247.5), properties["wind.deg"].as_f64());
+    assert!(properties["weather.1.main"].is_string());
+
+    let mut info = info;
+    info.coord = None;
+    assert!(info.to_geojson()["features"][0]["geometry"].is_null());
+}
+
+#[test]
+fn round_trip() {
+    let agg: WeatherAggregate =
+        serde_json::from_str(include_str!("../fixtures/find.json")).unwrap();
+    let body = agg.to_geojson().to_string();
+
+    let imported = WeatherAggregate::from_geojson(&body).unwrap();
+    assert_eq!(Some(2), imported.count);
+    assert_eq!(agg.to_geojson(), imported.to_geojson());
+
+    let list = imported.list.unwrap();
+    assert_eq!(Some(10.4036), list[0].coord.clone().unwrap().lon);
+    assert_eq!(Some(0.12), list[1].rain.clone().unwrap().one_hour);
+    assert_eq!("clear sky",
+               list[0].weather.clone().unwrap()[0].description.clone().unwrap());
+
+    let boxed = WeatherBoxAggregate::from_geojson(&body).unwrap();
+    assert_eq!(Some(2), boxed.cnt);
+
+    let single = WeatherInfo::from_geojson(&body).unwrap();
+    assert_eq!(Some(6542122), single.id);
+}
+
+#[test]
+fn round_trip_preserves_unknown_fields() {
+    let mut info: WeatherInfo =
+        serde_json::from_str(include_str!("../fixtures/weather_clear.json")).unwrap();
+    info.extra.insert("uvi".to_string(), json!(3.1));
+    info.extra.insert("alerts".to_string(), json!([{"event": "Wind"}, {"event": "Rain"}]));
+
+    let imported = WeatherInfo::from_geojson(&info.to_geojson().to_string()).unwrap();
+    assert_eq!(Some(&json!(3.1)), imported.extra.get("uvi"));
+    assert_eq!(Some(&json!([{"event": "Wind"}, {"event": "Rain"}])),
+               imported.extra.get("alerts"));
+
+    // Item 10 must not be mistaken for a gap after item 1
+    let alerts: Vec<_> = (0..12).map(|i| json!({"event": i})).collect();
+    info.extra.insert("alerts".to_string(), json!(alerts));
+    let imported = WeatherInfo::from_geojson(&info.to_geojson().to_string()).unwrap();
+    assert_eq!(Some(&json!(alerts)), imported.extra.get("alerts"));
+
+    // Empty arrays and objects are kept
+    info.extra.insert("alerts".to_string(), json!([]));
+    info.extra.insert("tags".to_string(), json!({}));
+    info.weather = Some(Vec::new());
+    let geojson = info.to_geojson();
+    assert_eq!(json!([]), geojson["features"][0]["properties"]["weather"]);
+    let imported = WeatherInfo::from_geojson(&geojson.to_string()).unwrap();
+    assert_eq!(Some(&json!([])), imported.extra.get("alerts"));
+    assert_eq!(Some(&json!({})), imported.extra.get("tags"));
+    assert_eq!(Some(0), imported.weather.map(|weather| weather.len()));
+}
+
+#[test]
+fn import_feature_id() {
+    let body = json!({
+        "type": "FeatureCollection",
+        "features": [
+            {"type": "Feature", "id": 2643743, "geometry": null, "properties": {"name": "London"}},
+            {"type": "Feature", "id": 1, "geometry": null, "properties": {"id": 3173435}},
+        ],
+    });
+
+    let agg = WeatherAggregate::from_geojson(&body.to_string()).unwrap();
+    let ids: Vec<_> = agg.list.unwrap().iter().map(|info| info.id).collect();
+    assert_eq!(vec![Some(2643743), Some(3173435)], ids);
+}
+
+#[test]
+fn import_rejects_other_documents() {
+    for body in &[r#"{"type": "Feature"}"#,
+                  r#"{"type": "FeatureCollection"}"#,
+                  r#"{"type": "FeatureCollection", "features": []}"#,
+                  "not json"] {
+        match WeatherInfo::from_geojson(body) {
+            Err(Error::JsonDecodeError(..)) => (),
+            res => panic!("unexpected result for {}: {:?}", body, res),
+        }
+    }
+
+    // Array indices leaving gaps, possibly huge, are rejected
+    for key in &["weather.1.id", "weather.4000000000", "weather.18446744073709551615.id"] {
+        let body = json!({
+            "type": "FeatureCollection",
+            "features": [{"type": "Feature", "geometry": null, "properties": {*key: 800}}],
+        });
+        match WeatherInfo::from_geojson(&body.to_string()) {
+            Err(Error::JsonDecodeError(..)) => (),
+            res => panic!("unexpected result for {}: {:?}", key, res),
+        }
+    }
+
+    let empty = WeatherBoxAggregate::from_geojson(r#"{"type": "FeatureCollection", "features": []}"#)
+        .unwrap();
+    assert_eq!(Some(0), empty.cnt);
+}
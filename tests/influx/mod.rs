@@ -0,0 +1,65 @@
+extern crate owm;
+extern crate serde_json;
+
+use self::owm::data::{WeatherAggregate, WeatherInfo};
+use self::owm::influx::{LineProtocol, Precision, FIELDS};
+
+fn clear() -> WeatherInfo {
+    serde_json::from_str(include_str!("../fixtures/weather_clear.json")).unwrap()
+}
+
+#[test]
+fn parse_precisions() {
+    assert_eq!(Ok(Precision::Milliseconds), "ms".parse());
+    assert_eq!("us", Precision::Microseconds.to_string());
+    assert!("m".parse::<Precision>().is_err());
+}
+
+#[test]
+fn all_fields() {
+    let line = LineProtocol::new().render(&clear());
+
+    assert!(line.starts_with("weather,city_id=2643743,city=London,country=GB \
+                              lat=51.5085,lon=-0.1257,weather_id=800i,temp=282.55,"));
+    assert!(line.contains(",pressure=1023.0,humidity=100i,"));
+    assert!(line.ends_with(",sunset=1560396563i,timezone=3600i 1560350645000000000\n"));
+    assert!(!line.contains("rain"));
+    assert_eq!(FIELDS.len() - 7, line.split(' ').nth(1).unwrap().split(',').count());
+}
+
+#[test]
+fn selection_and_precision() {
+    let influx = LineProtocol::new()
+        .measurement("current weather")
+        .fields(vec!["humidity", "temp"])
+        .unwrap()
+        .precision(Precision::Seconds);
+
+    assert_eq!("current\\ weather,city_id=2643743,city=London,country=GB humidity=100i,temp=282.55 \
+                1560350645\n",
+               influx.render(&clear()));
+    assert_eq!(Err("unknown field: dew_point".to_string()),
+               LineProtocol::new().fields(vec!["temp", "dew_point"]));
+}
+
+#[test]
+fn aggregates() {
+    let find: WeatherAggregate = serde_json::from_str(include_str!("../fixtures/find.json")).unwrap();
+    let influx = LineProtocol::new().fields(vec!["rain_1h", "clouds"]).unwrap();
+    let text = influx.render(&find);
+
+    assert_eq!(find.list.as_ref().unwrap().len(), text.lines().count());
+    assert!(text.lines().all(|line| line.starts_with("weather,city_id=")));
+
+    // Tags are escaped, missing tags and timestamps are left out
+    let info = WeatherInfo {
+        name: Some("Reggio, Emilia".to_string()),
+        visibility: Some(10000),
+        ..WeatherInfo::default()
+    };
+    assert_eq!("weather,city=Reggio\\,\\ Emilia visibility=10000i\n",
+               LineProtocol::new().render(&info));
+
+    // Observations without fields are skipped
+    assert_eq!("", influx.render(&info));
+}
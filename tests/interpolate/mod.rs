@@ -0,0 +1,152 @@
+extern crate owm;
+extern crate serde_json;
+
+use self::owm::{BoundingBox, Error, LatLon};
+use self::owm::data::{WeatherAggregate, WeatherBoxAggregate};
+use self::owm::interpolate::{Field, Interpolator, Method};
+
+fn box_city() -> WeatherBoxAggregate {
+    serde_json::from_value(json!({
+        "cod": 200,
+        "cnt": 3,
+        "list": [
+            {"id": 1, "coord": {"Lat": 44.0, "Lon": 10.0},
+             "main": {"temp": 10.0, "pressure": 1010.0, "humidity": 50},
+             "wind": {"speed": 2.0, "deg": 270.0}},
+            {"id": 2, "coord": {"Lat": 44.0, "Lon": 12.0},
+             "main": {"temp": 20.0, "pressure": 1020.0, "humidity": 70},
+             "wind": {"speed": 2.0, "deg": 90.0}},
+            {"id": 3, "coord": {"Lat": 42.0, "Lon": 10.0},
+             "main": {"humidity": 90}}
+        ]
+    }))
+        .unwrap()
+}
+
+fn assert_close(expected: f64, actual: f64) {
+    assert!((expected - actual).abs() < 1e-3,
+            "expected {}, got {}",
+            expected,
+            actual);
+}
+
+#[test]
+fn grid_layout() {
+    let bbox = BoundingBox::new(44.0, 42.0, 10.0, 12.0).unwrap();
+    let grid = Interpolator::new(Field::Temperature).grid(&box_city(), &bbox, 1.0).unwrap();
+
+    assert_eq!((3, 3), (grid.rows, grid.cols));
+    assert_eq!(9, grid.values.len());
+    assert_eq!(LatLon::new(44.0, 10.0).ok(), grid.point(0, 0));
+    assert_eq!(LatLon::new(42.0, 12.0).ok(), grid.point(2, 2));
+    assert_eq!(None, grid.get(3, 0));
+    assert_eq!(None, grid.point(0, 3));
+
+    let grid = Interpolator::new(Field::Temperature).grid(&box_city(), &bbox, 0.75).unwrap();
+    assert_eq!((3, 3), (grid.rows, grid.cols));
+    assert_close(42.5, grid.point(2, 0).unwrap().lat());
+}
+
+#[test]
+fn inverse_distance_weighting() {
+    let bbox = BoundingBox::new(44.0, 42.0, 10.0, 12.0).unwrap();
+    let grid = Interpolator::new(Field::Temperature).grid(&box_city(), &bbox, 1.0).unwrap();
+
+    // Observations are reproduced exactly, and the point halfway between
+    // them is their average
+    assert_close(10.0, grid.get(0, 0).unwrap());
+    assert_close(20.0, grid.get(0, 2).unwrap());
+    assert_close(15.0, grid.get(0, 1).unwrap());
+
+    // Observations lacking the field are ignored
+    let value = grid.get(2, 0).unwrap();
+    assert!(value > 10.0 && value < 15.0);
+
+    let humidity = Interpolator::new(Field::Humidity).grid(&box_city(), &bbox, 1.0).unwrap();
+    assert_close(90.0, humidity.get(2, 0).unwrap());
+}
+
+#[test]
+fn nearest_neighbour() {
+    let bbox = BoundingBox::new(44.0, 42.0, 10.0, 12.0).unwrap();
+    let grid = Interpolator::new(Field::Pressure)
+        .method(Method::Nearest)
+        .grid(&box_city(), &bbox, 0.5).unwrap();
+
+    assert_close(1010.0, grid.get(0, 1).unwrap());
+    assert_close(1020.0, grid.get(0, 3).unwrap());
+    assert_close(1010.0, grid.get(4, 0).unwrap());
+}
+
+#[test]
+fn mask_beyond_max_distance() {
+    let bbox = BoundingBox::new(44.0, 42.0, 10.0, 12.0).unwrap();
+    let grid = Interpolator::new(Field::Temperature)
+        .max_distance(150.0)
+        .grid(&box_city(), &bbox, 1.0).unwrap();
+
+    assert_close(10.0, grid.get(0, 0).unwrap());
+    assert_close(10.0, grid.get(1, 0).unwrap());
+    assert!(grid.get(2, 2).unwrap().is_nan());
+
+    let nearest = Interpolator::new(Field::Temperature)
+        .method(Method::Nearest)
+        .max_distance(100.0);
+    assert!(nearest.at(&box_city(), &LatLon::new(30.0, 11.0).unwrap()).is_nan());
+}
+
+#[test]
+fn wind_components() {
+    let interpolator = Interpolator::new(Field::WindU);
+    let west = LatLon::new(44.0, 10.0).unwrap();
+
+    // Wind from the west blows eastward
+    assert_close(2.0, interpolator.at(&box_city(), &west));
+    assert_close(0.0, interpolator.at(&box_city(), &LatLon::new(44.0, 11.0).unwrap()));
+    assert_close(0.0, Interpolator::new(Field::WindV).at(&box_city(), &west));
+}
+
+#[test]
+fn circle_aggregate_and_empty_input() {
+    let agg: WeatherAggregate =
+        serde_json::from_str(include_str!("../fixtures/find.json")).unwrap();
+    let bbox = BoundingBox::new(44.0, 43.0, 10.0, 11.0).unwrap();
+    let grid = Interpolator::new(Field::Temperature).grid(&agg, &bbox, 0.5).unwrap();
+    assert!(grid.values.iter().all(|v| v.is_finite()));
+
+    let empty = WeatherBoxAggregate::default();
+    let grid = Interpolator::new(Field::Temperature).grid(&empty, &bbox, 1.0).unwrap();
+    assert!(grid.values.iter().all(|v| v.is_nan()));
+}
+
+#[test]
+fn grid_across_antimeridian() {
+    let bbox = BoundingBox::new(-10.0, -12.0, 179.0, -179.0).unwrap();
+    let grid = Interpolator::new(Field::Temperature).grid(&box_city(), &bbox, 1.0).unwrap();
+
+    assert_eq!(3, grid.cols);
+    assert_eq!(LatLon::new(-10.0, -180.0).ok(), grid.point(0, 1));
+    assert_eq!(LatLon::new(-10.0, -179.0).ok(), grid.point(0, 2));
+}
+
+#[test]
+fn invalid_grids() {
+    let interpolator = Interpolator::new(Field::Temperature);
+    let bbox = BoundingBox::new(44.0, 42.0, 10.0, 12.0).unwrap();
+    let invalid_boxes = [BoundingBox { top: 91.0, ..bbox },
+                         BoundingBox { bottom: 45.0, ..bbox },
+                         BoundingBox { left: f64::NAN, ..bbox }];
+
+    for invalid in &invalid_boxes {
+        match interpolator.grid(&box_city(), invalid, 1.0) {
+            Err(Error::InvalidCoordinates(_)) => (),
+            res => panic!("unexpected result for {:?}: {:?}", invalid, res),
+        }
+    }
+    for &resolution in &[0.0, -1.0, f64::NAN, f64::INFINITY, 1e-300] {
+        match interpolator.grid(&box_city(), &bbox, resolution) {
+            Err(Error::InvalidCoordinates(_)) => (),
+            res => panic!("unexpected result for {}: {:?}", resolution, res),
+        }
+    }
+}
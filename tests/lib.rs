@@ -1,2 +1,54 @@
+#[macro_use]
+extern crate serde_json;
+
 #[cfg(test)]
-mod current;
\ No newline at end of file
+mod mock;
+
+#[cfg(test)]
+mod batch;
+#[cfg(test)]
+mod city;
+#[cfg(all(test, feature = "cli"))]
+mod cli;
+#[cfg(test)]
+mod config;
+#[cfg(test)]
+mod current;
+#[cfg(test)]
+mod data;
+#[cfg(test)]
+mod derived;
+#[cfg(test)]
+mod format;
+#[cfg(all(test, feature = "exporter"))]
+mod exporter;
+#[cfg(test)]
+mod geo;
+#[cfg(test)]
+mod geojson;
+#[cfg(test)]
+mod influx;
+#[cfg(test)]
+mod interpolate;
+#[cfg(test)]
+mod location;
+#[cfg(all(test, feature = "mqtt"))]
+mod mqtt;
+#[cfg(test)]
+mod observations;
+#[cfg(test)]
+mod prometheus;
+#[cfg(test)]
+mod rules;
+#[cfg(all(test, feature = "sqlite"))]
+mod store;
+#[cfg(test)]
+mod template;
+#[cfg(all(test, feature = "chrono"))]
+mod time;
+#[cfg(test)]
+mod tiles;
+#[cfg(test)]
+mod watch;
+#[cfg(test)]
+mod wind;
@@ -0,0 +1,102 @@
+extern crate owm;
+extern crate serde_json;
+
+use self::owm::{Error, Location};
+use mock::{MockRequest, MockServer};
+
+fn weather(_: &MockRequest) -> (u16, String) {
+    (200, include_str!("../fixtures/weather_clear.json").to_string())
+}
+
+/// Parameters of the single request sent to query the given location.
+fn params(location: &Location) -> Vec<(String, String)> {
+    let server = MockServer::new(weather);
+    server.hub().current().at(location).unwrap();
+
+    let requests = server.requests();
+    assert_eq!(1, requests.len());
+    assert_eq!("/data/2.5/weather", requests[0].path);
+
+    requests[0].params.iter().filter(|(k, _)| k != "appid").cloned().collect()
+}
+
+fn param(key: &str, value: &str) -> Vec<(String, String)> {
+    vec![(key.to_string(), value.to_string())]
+}
+
+#[test]
+fn query_by_location() {
+    assert_eq!(param("q", "London"), params(&Location::name("London", None)));
+    assert_eq!(param("q", "London,GB"), params(&Location::name("London", Some("GB"))));
+    assert_eq!(param("q", "Portland,OR,US"),
+               params(&Location::Name {
+                   city: "Portland".to_string(),
+                   state: Some("OR".to_string()),
+                   country: Some("US".to_string()),
+               }));
+    assert_eq!(param("q", "Portland,OR,US"),
+               params(&Location::Name {
+                   city: "Portland".to_string(),
+                   state: Some("OR".to_string()),
+                   country: None,
+               }));
+    assert_eq!(param("id", "2643743"), params(&Location::id(2643743)));
+    assert_eq!(param("zip", "SW1A 1AA,GB"),
+               params(&Location::postal_code("SW1A 1AA", Some("GB"))));
+    assert_eq!(param("zip", "01234"), params(&Location::postal_code("01234", None)));
+
+    let mut coords = params(&Location::coords(43.7085, 370.4036).unwrap());
+    coords.sort();
+    assert_eq!(vec![("lat".to_string(), "43.7085".to_string()),
+                    ("lon".to_string(), "10.4036".to_string())],
+               coords);
+}
+
+#[test]
+fn zip_code_delegates_to_postal_code() {
+    let server = MockServer::new(weather);
+    server.hub().current().by_zip_code(56124, Some("IT")).unwrap();
+    server.hub().current().by_postal_code("56124", Some("IT")).unwrap();
+
+    let mut requests = server.requests();
+    for request in &mut requests {
+        request.params.sort();
+    }
+    assert_eq!(requests[0].params, requests[1].params);
+}
+
+#[test]
+fn invalid_coordinates() {
+    match Location::coords(91.0, 0.0) {
+        Err(Error::InvalidCoordinates(_)) => (),
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
+
+#[test]
+fn serialize_locations() {
+    let locations = vec![Location::name("London", Some("GB")),
+                         Location::id(2643743),
+                         Location::postal_code("SW1A 1AA", Some("GB")),
+                         Location::coords(51.5, -0.12).unwrap()];
+
+    let json = serde_json::to_value(&locations).unwrap();
+    assert_eq!(json!([
+                   {"type": "name", "city": "London", "state": null, "country": "GB"},
+                   {"type": "id", "id": 2643743},
+                   {"type": "postal_code", "code": "SW1A 1AA", "country": "GB"},
+                   {"type": "coords", "lat": 51.5, "lon": -0.12}
+               ]),
+               json);
+
+    let parsed: Vec<Location> = serde_json::from_value(json).unwrap();
+    assert_eq!(locations, parsed);
+
+    let parsed: Location = serde_json::from_str(r#"{"type": "name", "city": "Pisa"}"#).unwrap();
+    assert_eq!(Location::name("Pisa", None), parsed);
+
+    let parsed: Location = serde_json::from_str(r#"{"type": "coords", "lat": 43.7, "lon": 370.4}"#)
+        .unwrap();
+    assert_eq!(Location::coords(43.7, 370.4).unwrap(), parsed);
+    assert!(serde_json::from_str::<Location>(r#"{"type": "coords", "lat": 91, "lon": 0}"#).is_err());
+}
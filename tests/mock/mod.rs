@@ -0,0 +1,92 @@
+//! Minimal HTTP server standing in for the OWM API in offline tests.
+
+extern crate hyper;
+extern crate owm;
+extern crate url;
+
+use std::io::Read;
+use std::mem;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use self::hyper::server::{Request, Response, Server};
+use self::hyper::status::StatusCode;
+use self::hyper::uri::RequestUri;
+use self::owm::WeatherHub;
+
+/// Request received by the mock server: endpoint path and query parameters.
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    pub path: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl MockRequest {
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.params.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+pub struct MockServer {
+    socket: SocketAddr,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    /// Serves the responses returned by `handler` as (status, body) pairs.
+    pub fn new<F>(handler: F) -> MockServer
+        where F: Fn(&MockRequest) -> (u16, String) + Send + Sync + 'static
+    {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+
+        // Without keep-alive, idle client connections don't hold on to the handler threads
+        let mut server = Server::http("127.0.0.1:0").unwrap();
+        server.keep_alive(None);
+
+        let listening = server.handle_threads(move |mut req: Request, mut res: Response| {
+                let mut body = String::new();
+                req.read_to_string(&mut body).unwrap();
+
+                let uri = match req.uri {
+                    RequestUri::AbsolutePath(ref path) => path.clone(),
+                    ref uri => panic!("unexpected URI: {:?}", uri),
+                };
+                let mut parts = uri.splitn(2, '?');
+                let request = MockRequest {
+                    path: parts.next().unwrap().to_string(),
+                    params: url::form_urlencoded::parse(parts.next().unwrap_or("").as_bytes())
+                        .into_owned()
+                        .collect(),
+                };
+                log.lock().unwrap().push(request.clone());
+
+                let (status, body) = handler(&request);
+                *res.status_mut() = StatusCode::from_u16(status);
+                res.send(body.as_bytes()).unwrap();
+            }, 4)
+            .unwrap();
+
+        // Dropping the listener would wait forever for the server thread to exit
+        let socket = listening.socket;
+        mem::forget(listening);
+
+        MockServer {
+            socket: socket,
+            requests: requests,
+        }
+    }
+
+    /// Creates a hub sending its requests to this server.
+    pub fn hub(&self) -> WeatherHub {
+        WeatherHub::new(hyper::Client::new(), "test-key").base_url(&self.url())
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}/data", self.socket)
+    }
+
+    /// Requests received so far, in order.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,167 @@
+extern crate owm;
+extern crate serde_json;
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use self::owm::data::WeatherInfo;
+use self::owm::mqtt::{Messages, MqttOptions, Publisher};
+use self::owm::{Error, ErrorKind, Units};
+
+fn clear() -> WeatherInfo {
+    serde_json::from_str(include_str!("../fixtures/weather_clear.json")).unwrap()
+}
+
+fn rain() -> WeatherInfo {
+    serde_json::from_str(include_str!("../fixtures/weather_rain.json")).unwrap()
+}
+
+fn json(payload: &[u8]) -> serde_json::Value {
+    serde_json::from_slice(payload).unwrap()
+}
+
+/// Message received by the broker: topic, payload and retain flag.
+type Published = (String, Vec<u8>, bool);
+
+/// Minimal MQTT 3.1.1 broker accepting a single client. It acknowledges
+/// publishes slowly, and returns the messages published and whether the
+/// client sent DISCONNECT, rather than closing the socket.
+fn broker() -> (u16, JoinHandle<(Vec<Published>, bool)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let broker = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut published = Vec::new();
+        loop {
+            let mut header = [0u8];
+            if stream.read_exact(&mut header).is_err() {
+                return (published, false);
+            }
+            let mut body = vec![0; remaining_length(&mut stream)];
+            stream.read_exact(&mut body).unwrap();
+
+            match header[0] >> 4 {
+                // CONNECT, answered by CONNACK
+                1 => stream.write_all(&[0x20, 2, 0, 0]).unwrap(),
+                // PUBLISH, answered by PUBACK above QoS 0
+                3 => {
+                    let topic_end = 2 + u16::from_be_bytes([body[0], body[1]]) as usize;
+                    let mut payload_start = topic_end;
+                    if header[0] & 0b0110 != 0 {
+                        thread::sleep(Duration::from_millis(20));
+                        stream.write_all(&[0x40, 2, body[topic_end], body[topic_end + 1]]).unwrap();
+                        payload_start += 2;
+                    }
+                    published.push((String::from_utf8(body[2..topic_end].to_vec()).unwrap(),
+                                    body[payload_start..].to_vec(),
+                                    header[0] & 1 == 1));
+                }
+                // PINGREQ, answered by PINGRESP
+                12 => stream.write_all(&[0xd0, 0]).unwrap(),
+                // DISCONNECT
+                14 => return (published, true),
+                _ => (),
+            }
+        }
+    });
+
+    (port, broker)
+}
+
+fn remaining_length(stream: &mut TcpStream) -> usize {
+    let (mut length, mut shift) = (0, 0);
+    loop {
+        let mut byte = [0u8];
+        stream.read_exact(&mut byte).unwrap();
+        length += ((byte[0] & 0x7f) as usize) << shift;
+        if byte[0] & 0x80 == 0 {
+            return length;
+        }
+        shift += 7;
+    }
+}
+
+#[test]
+fn state_messages() {
+    let (topic, payload) = Messages::new().state(&clear()).unwrap();
+    assert_eq!("owm/2643743", topic);
+    assert_eq!(282.55, json(&payload)["main"]["temp"]);
+
+    let messages = Messages::new().state_topic("home/weather/{city}/{city_id}");
+    let mut info = rain();
+    info.name = Some("Reggio Emilia".to_string());
+    assert_eq!("home/weather/reggio_emilia/3163858", messages.state(&info).unwrap().0);
+
+    match Messages::new().state(&WeatherInfo::default()) {
+        Err(err @ Error::InvalidObservation(_)) => assert_eq!(ErrorKind::InvalidRequest, err.kind()),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn discovery_messages() {
+    let messages = Messages::new().discovery_prefix("ha").units(Units::Imperial);
+    let discovery = messages.discovery(&clear()).unwrap();
+
+    let topics: Vec<&str> = discovery.iter().map(|(topic, _)| topic.as_str()).collect();
+    assert_eq!(vec!["ha/sensor/owm_2643743/temperature/config",
+                    "ha/sensor/owm_2643743/humidity/config",
+                    "ha/sensor/owm_2643743/pressure/config",
+                    "ha/sensor/owm_2643743/wind_speed/config",
+                    "ha/sensor/owm_2643743/wind_bearing/config",
+                    "ha/sensor/owm_2643743/condition/config"],
+               topics);
+
+    let temperature = json(&discovery[0].1);
+    assert_eq!("owm_2643743_temperature", temperature["unique_id"]);
+    assert_eq!("owm/2643743", temperature["state_topic"]);
+    assert_eq!("{{ value_json.main.temp }}", temperature["value_template"]);
+    assert_eq!("°F", temperature["unit_of_measurement"]);
+    assert_eq!("temperature", temperature["device_class"]);
+    assert_eq!("measurement", temperature["state_class"]);
+    assert_eq!("London", temperature["device"]["name"]);
+    assert_eq!("owm_2643743", temperature["device"]["identifiers"][0]);
+
+    assert_eq!("mph", json(&discovery[3].1)["unit_of_measurement"]);
+    let condition = json(&discovery[5].1);
+    assert!(condition.get("unit_of_measurement").is_none());
+    assert!(condition.get("device_class").is_none());
+}
+
+#[test]
+fn publish_to_broker() {
+    let (port, broker) = broker();
+    let mut publisher = Publisher::connect(MqttOptions::new("owm-test", "127.0.0.1", port),
+                                           Messages::new())
+        .unwrap()
+        .retain(true);
+
+    publisher.publish(&clear()).unwrap();
+    publisher.publish(&clear()).unwrap();
+    publisher.publish(&rain()).unwrap();
+    assert!(publisher.publish(&WeatherInfo::default()).is_err());
+    publisher.disconnect().unwrap();
+
+    let (published, disconnected) = broker.join().unwrap();
+    assert!(disconnected);
+    let topics: Vec<&str> = published.iter().map(|(topic, _, _)| topic.as_str()).collect();
+    assert_eq!(15, published.len());
+    assert_eq!("homeassistant/sensor/owm_2643743/temperature/config", topics[0]);
+    assert_eq!(&["owm/2643743", "owm/2643743"], &topics[6..8]);
+    assert_eq!("homeassistant/sensor/owm_3163858/temperature/config", topics[8]);
+    assert_eq!("owm/3163858", topics[14]);
+    assert!(published.iter().all(|&(_, _, retain)| retain));
+    assert_eq!("Zocca", json(&published[14].1)["name"]);
+}
+
+#[test]
+fn broker_unreachable() {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+    match Publisher::connect(MqttOptions::new("owm-test", "127.0.0.1", port), Messages::new()) {
+        Err(err) => assert_eq!(ErrorKind::Network, err.kind()),
+        Ok(_) => panic!("connected to a closed port"),
+    }
+}
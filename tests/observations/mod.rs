@@ -0,0 +1,115 @@
+extern crate owm;
+extern crate serde_json;
+
+use self::owm::LatLon;
+use self::owm::data::{Field, Observations, Stats, WeatherAggregate, WeatherBoxAggregate,
+                      WeatherInfo};
+
+fn box_city() -> WeatherBoxAggregate {
+    serde_json::from_value(json!({
+        "cod": 200,
+        "cnt": 5,
+        "list": [
+            {"id": 1, "name": "Livorno", "coord": {"Lat": 43.55, "Lon": 10.3167},
+             "main": {"temp": 18.0, "humidity": 60}, "wind": {"speed": 6.5, "deg": 200}},
+            {"id": 2, "name": "Pisa", "coord": {"Lat": 43.7085, "Lon": 10.4036},
+             "main": {"temp": 21.0, "humidity": 55}, "wind": {"speed": 2.0, "deg": 180}},
+            {"id": 3, "name": "Firenze", "coord": {"Lat": 43.77925, "Lon": 11.24626},
+             "main": {"temp": 24.0, "humidity": 40}, "wind": {"speed": 1.0, "deg": 90}},
+            {"id": 4, "name": "Lucca", "coord": {"Lat": 43.8433, "Lon": 10.5027},
+             "main": {"temp": 21.0}},
+            {"id": 5, "name": "Siena"}
+        ]
+    }))
+        .unwrap()
+}
+
+fn names(list: &[&WeatherInfo]) -> Vec<String> {
+    list.iter().map(|info| info.name.clone().unwrap()).collect()
+}
+
+#[test]
+fn iterate_both_aggregates() {
+    let agg: WeatherAggregate =
+        serde_json::from_str(include_str!("../fixtures/find.json")).unwrap();
+    assert_eq!(2, agg.iter().count());
+    assert_eq!(5, box_city().iter().count());
+
+    assert_eq!(0, WeatherAggregate::default().iter().count());
+    assert_eq!(None, WeatherBoxAggregate::default().hottest().map(|i| i.id));
+}
+
+#[test]
+fn sort_and_filter() {
+    let agg = box_city();
+
+    let sorted = agg.sorted_by(Field::Temperature);
+    assert_eq!(vec!["Livorno", "Pisa", "Lucca", "Firenze", "Siena"], names(&sorted));
+
+    let sorted = agg.sorted_by(Field::Humidity);
+    assert_eq!(vec!["Firenze", "Pisa", "Livorno", "Lucca", "Siena"], names(&sorted));
+
+    let warm = agg.filter_by(Field::Temperature, |t| t > 20.0);
+    assert_eq!(vec!["Pisa", "Firenze", "Lucca"], names(&warm));
+
+    let calm = agg.filter_by(Field::WindSpeed, |s| s < 3.0);
+    assert_eq!(vec!["Pisa", "Firenze"], names(&calm));
+}
+
+#[test]
+fn nearest_to_point() {
+    let agg = box_city();
+
+    let (info, distance) = agg.nearest(&LatLon::new(43.72, 10.40).unwrap()).unwrap();
+    assert_eq!(Some("Pisa".to_string()), info.name);
+    assert!(distance < 2.0);
+
+    let (info, _) = agg.nearest(&LatLon::new(44.5, 12.0).unwrap()).unwrap();
+    assert_eq!(Some("Firenze".to_string()), info.name);
+}
+
+#[test]
+fn extremes() {
+    let agg = box_city();
+
+    assert_eq!(Some(3), agg.hottest().and_then(|i| i.id));
+    assert_eq!(Some(1), agg.coldest().and_then(|i| i.id));
+    assert_eq!(Some(1), agg.windiest().and_then(|i| i.id));
+    assert_eq!(Some(3), agg.lowest(Field::WindSpeed).and_then(|i| i.id));
+
+    // Ties go to the first observation
+    let tied = agg.filter_by(Field::Temperature, |t| t == 21.0);
+    let tied: Vec<WeatherInfo> = tied.into_iter().cloned().collect();
+    assert_eq!(Some(2), tied.hottest().and_then(|i| i.id));
+    assert_eq!(Some(2), tied.coldest().and_then(|i| i.id));
+}
+
+#[test]
+fn summary_statistics() {
+    let agg = box_city();
+
+    let stats = agg.stats(Field::Temperature).unwrap();
+    assert_eq!(4, stats.count);
+    assert_eq!((18.0, 24.0), (stats.min, stats.max));
+    assert_eq!(21.0, stats.mean);
+    assert_eq!(21.0, stats.median);
+    assert!((stats.std_dev - 4.5f64.sqrt()).abs() < 1e-9);
+
+    assert_eq!(Some(18.0), agg.percentile(Field::Temperature, 0.0));
+    assert_eq!(Some(24.0), agg.percentile(Field::Temperature, 100.0));
+    assert_eq!(Some(19.125), agg.percentile(Field::Temperature, 12.5));
+
+    assert_eq!(Some(55.0), agg.stats(Field::Humidity).map(|s| s.median));
+    assert_eq!(None, agg.stats(Field::Snow));
+}
+
+#[test]
+fn stats_of_raw_values() {
+    let stats = Stats::new(vec![4.0, 1.0, f64::NAN, 3.0, 2.0]).unwrap();
+
+    assert_eq!(4, stats.count);
+    assert_eq!(2.5, stats.median);
+    assert_eq!(1.75, stats.percentile(25.0));
+    assert_eq!(4.0, stats.percentile(150.0));
+    assert_eq!(None, Stats::new(vec![]));
+}
@@ -0,0 +1,82 @@
+extern crate owm;
+extern crate serde_json;
+
+use std::time::Duration;
+use self::owm::data::{WeatherAggregate, WeatherInfo};
+use self::owm::prometheus::{write_location_up, write_stats, write_weather};
+use self::owm::{ErrorKind, FormatResponse, HubStats, Location, Units};
+use mock::{MockRequest, MockServer};
+
+fn render<F: FnOnce(&mut Vec<u8>) -> std::io::Result<()>>(write: F) -> String {
+    let mut out = Vec::new();
+    write(&mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn weather_gauges() {
+    let find: WeatherAggregate = serde_json::from_str(include_str!("../fixtures/find.json")).unwrap();
+    let list = find.list.unwrap();
+    let rain: WeatherInfo =
+        serde_json::from_str(include_str!("../fixtures/weather_rain.json")).unwrap();
+
+    let text = render(|out| write_weather(out, vec![("home", &list[0]), ("lab \"2\"", &rain)]));
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!("# HELP owm_temperature_celsius Temperature", lines[0]);
+    assert_eq!("# TYPE owm_temperature_celsius gauge", lines[1]);
+    assert_eq!("owm_temperature_celsius{location=\"home\",city_id=\"6542122\",city=\"Pisa\"} 12.38",
+               lines[2]);
+    assert!(lines[3].starts_with("owm_temperature_celsius{location=\"lab \\\"2\\\"\",city_id=\"3163858\""));
+    assert!(text.contains("owm_rain_1h_millimeters{location=\"home\",city_id=\"6542122\",city=\"Pisa\"} 0\n"));
+    assert!(text.contains("owm_rain_1h_millimeters{location=\"lab \\\"2\\\"\",city_id=\"3163858\",city=\"Zocca\"} 0.25\n"));
+    assert!(text.contains("owm_observation_timestamp_seconds{location=\"home\",city_id=\"6542122\",city=\"Pisa\"} 1697630400\n"));
+
+    // Missing fields are left out, except for precipitation
+    let empty = render(|out| write_weather(out, vec![("void", &WeatherInfo::default())]));
+    assert_eq!(2, empty.lines().filter(|line| !line.starts_with('#')).count());
+    assert!(empty.contains("owm_snow_1h_millimeters{location=\"void\",city_id=\"\",city=\"\"} 0\n"));
+}
+
+#[test]
+fn health_metrics() {
+    let mut stats = HubStats {
+        requests: 3,
+        latency: Duration::from_millis(1500),
+        cache_hits: 2,
+        ..HubStats::default()
+    };
+    stats.errors.insert(ErrorKind::NotFound, 1);
+
+    let text = render(|out| write_stats(out, &stats));
+    assert!(text.contains("# TYPE owm_api_requests_total counter\nowm_api_requests_total 3\n"));
+    assert!(text.contains("owm_api_request_duration_seconds_sum 1.5\n"));
+    assert!(text.contains("owm_api_request_duration_seconds_count 3\n"));
+    assert!(text.contains("owm_api_errors_total{kind=\"not_found\"} 1\n"));
+    assert!(text.contains("owm_api_errors_total{kind=\"rate_limited\"} 0\n"));
+    assert!(text.contains("owm_cache_hits_total 2\n"));
+
+    let up = render(|out| write_location_up(out, vec![("home", true), ("gym", false)]));
+    assert!(up.ends_with("owm_location_up{location=\"home\"} 1\nowm_location_up{location=\"gym\"} 0\n"));
+}
+
+#[test]
+fn hub_stats() {
+    let server = MockServer::new(|req: &MockRequest| match req.param("id") {
+        Some("404") => (404, include_str!("../fixtures/error_not_found.json").to_string()),
+        _ => (200, include_str!("../fixtures/weather_clear.json").to_string()),
+    });
+    let hub = server.hub().cache(Duration::from_secs(60), 10);
+
+    hub.current().at(&Location::id(1)).unwrap();
+    hub.current().at(&Location::id(1)).unwrap();
+    hub.current().units(Units::Metric).at(&Location::id(1)).unwrap();
+    assert!(hub.current().at(&Location::id(404)).is_err());
+
+    let stats = hub.stats();
+    assert_eq!(3, stats.requests);
+    assert_eq!(1, stats.cache_hits);
+    assert_eq!(Some(&1), stats.errors.get(&ErrorKind::NotFound));
+    assert_eq!(1, stats.errors.len());
+    assert!(stats.latency > Duration::from_secs(0));
+}
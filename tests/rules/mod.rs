@@ -0,0 +1,137 @@
+extern crate owm;
+extern crate serde_json;
+
+use std::time::Duration;
+use self::owm::data::WeatherInfo;
+use self::owm::rules::{Alert, AlertState, Condition, Rule, RuleEngine};
+use self::owm::{Error, Units};
+
+/// Observation of Pisa at `dt`, in metric units.
+fn obs(dt: i64, temp: f32, gust: f32, rain: Option<f32>) -> WeatherInfo {
+    let mut value = json!({
+        "id": 6542122,
+        "name": "Pisa",
+        "dt": dt,
+        "main": {"temp": temp, "humidity": 80},
+        "wind": {"speed": 5.0, "gust": gust},
+    });
+    if let Some(rain) = rain {
+        value["rain"] = json!({"1h": rain});
+    }
+
+    serde_json::from_value(value).unwrap()
+}
+
+fn states(alerts: &[Alert]) -> Vec<(&str, AlertState)> {
+    alerts.iter().map(|alert| (alert.rule.as_str(), alert.state)).collect()
+}
+
+#[test]
+fn conditions() {
+    let eval = |text: &str, info: &WeatherInfo| Condition::parse(text).unwrap().eval(info, Units::Metric);
+    let cold_rain = obs(0, -1.5, 10.0, Some(0.3));
+    let warm = obs(0, 12.0, 10.0, None);
+
+    assert!(eval("temp < 0 and rain present", &cold_rain));
+    assert!(!eval("temp < 0 and rain present", &warm));
+    assert!(eval("temp<-1 or wind_gust >= 20", &cold_rain));
+    assert!(eval("not (temp > 10 or rain > 0.5)", &cold_rain));
+    assert!(eval("humidity == 80 and wind_speed != 4", &warm));
+    assert!(eval("dew_point < 9 and not condition present", &warm));
+
+    // Missing fields never compare, in either direction
+    assert!(!eval("rain > 0", &warm));
+    assert!(!eval("rain <= 0", &warm));
+    assert!(eval("not snow present", &warm));
+
+    // Volume over 3 hours when the last hour isn't reported
+    let rain_3h: WeatherInfo =
+        serde_json::from_str(include_str!("../fixtures/weather_rain_3h.json")).unwrap();
+    assert!(eval("rain present", &rain_3h));
+    assert!(eval("rain == 0.75", &rain_3h));
+}
+
+#[test]
+fn invalid_conditions() {
+    for &(text, message) in &[("temp <", "expected a comparison or present after temp at position 5"),
+                              ("temperature < 0", "unknown field temperature at position 0"),
+                              ("temp < 0 and", "expected a field, not or ( at position 12"),
+                              ("(temp < 0", "unclosed parenthesis at position 0"),
+                              ("temp = 0", "unknown operator ="),
+                              ("temp < 0 rain present", "unexpected Ident(\"rain\")"),
+                              ("temp < 1.2.3", "invalid number 1.2.3")] {
+        match Condition::parse(text) {
+            Err(Error::InvalidRule(msg)) => {
+                assert!(msg.starts_with(message), "{}: {}", text, msg);
+                assert!(msg.ends_with(&format!("in \"{}\"", text)));
+            }
+            other => panic!("{}: {:?}", text, other),
+        }
+    }
+
+    assert!(Rule::new("gusts", "wind_gust > 20").unwrap().resolve_when("wind_gust <").is_err());
+}
+
+#[test]
+fn consecutive_readings_and_hysteresis() {
+    let mut engine = RuleEngine::new(Units::Metric)
+        .rule(Rule::new("gusts", "wind_gust > 20")
+            .unwrap()
+            .for_readings(2)
+            .resolve_when("wind_gust < 15")
+            .unwrap());
+
+    assert!(engine.observe(&obs(0, 10.0, 22.0, None)).is_empty());
+    assert!(engine.observe(&obs(600, 10.0, 18.0, None)).is_empty());
+    assert!(engine.observe(&obs(1200, 10.0, 21.0, None)).is_empty());
+
+    let alerts = engine.observe(&obs(1800, 10.0, 25.0, None));
+    assert_eq!(vec![("gusts", AlertState::Firing)], states(&alerts));
+    assert_eq!((Some(6542122), Some("Pisa".to_string()), Some(1800)),
+               (alerts[0].city_id, alerts[0].city.clone(), alerts[0].dt));
+    assert_eq!(vec!["gusts"], engine.firing(Some(6542122)));
+
+    // Below the firing threshold, but not yet below the resolve threshold
+    assert!(engine.observe(&obs(2400, 10.0, 17.0, None)).is_empty());
+    assert_eq!(vec![("gusts", AlertState::Resolved)],
+               states(&engine.observe(&obs(3000, 10.0, 12.0, None))));
+    assert!(engine.firing(Some(6542122)).is_empty());
+}
+
+#[test]
+fn durations_and_cooldowns() {
+    let mut engine = RuleEngine::new(Units::Metric)
+        .rule(Rule::new("freezing rain", "temp < 0 and rain present")
+            .unwrap()
+            .for_duration(Duration::from_secs(1200))
+            .cooldown(Duration::from_secs(3600)))
+        .rule(Rule::new("rain", "rain present").unwrap());
+
+    assert_eq!(vec![("rain", AlertState::Firing)],
+               states(&engine.observe(&obs(0, -1.0, 0.0, Some(0.2)))));
+    assert!(engine.observe(&obs(600, -1.0, 0.0, Some(0.2))).is_empty());
+    assert_eq!(vec![("freezing rain", AlertState::Firing)],
+               states(&engine.observe(&obs(1200, -1.0, 0.0, Some(0.2)))));
+    assert_eq!(vec![("freezing rain", AlertState::Resolved), ("rain", AlertState::Resolved)],
+               states(&engine.observe(&obs(1800, 2.0, 0.0, None))));
+
+    // Held long enough again, but within an hour of the previous alert
+    for dt in &[2400, 3000, 3600, 4200] {
+        let alerts = engine.observe(&obs(*dt, -1.0, 0.0, Some(0.2)));
+        assert!(alerts.iter().all(|alert| alert.rule == "rain"));
+    }
+    assert_eq!(vec![("freezing rain", AlertState::Firing)],
+               states(&engine.observe(&obs(4800, -1.0, 0.0, Some(0.2)))));
+}
+
+#[test]
+fn cities_are_independent() {
+    let mut engine = RuleEngine::new(Units::Metric).rule(Rule::new("cold", "temp < 0").unwrap());
+    let mut livorno = obs(0, -2.0, 0.0, None);
+    livorno.id = Some(3174659);
+
+    assert_eq!(1, engine.observe(&livorno).len());
+    assert!(engine.observe(&obs(0, 5.0, 0.0, None)).is_empty());
+    assert_eq!(vec!["cold"], engine.firing(Some(3174659)));
+    assert!(engine.firing(Some(6542122)).is_empty());
+}
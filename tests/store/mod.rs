@@ -0,0 +1,117 @@
+extern crate owm;
+extern crate serde_json;
+
+use self::owm::data::{Field, WeatherAggregate, WeatherInfo};
+use self::owm::store::{DailyRollup, Store};
+use self::owm::{Error, ErrorKind};
+
+fn clear() -> WeatherInfo {
+    serde_json::from_str(include_str!("../fixtures/weather_clear.json")).unwrap()
+}
+
+/// London at the given time and temperature, 1h ahead of UTC.
+fn london(dt: i64, temp: f32) -> WeatherInfo {
+    let mut info = clear();
+    info.dt = Some(dt);
+    info.main.as_mut().unwrap().temp = Some(temp);
+    info
+}
+
+#[test]
+fn deduplication() {
+    let mut store = Store::in_memory().unwrap();
+
+    assert!(store.insert(&clear()).unwrap());
+    assert!(!store.insert(&london(1560350645, 300.0)).unwrap());
+    assert!(store.insert(&london(1560351245, 283.0)).unwrap());
+
+    let stored = store.range(2643743, 0..i64::MAX).unwrap();
+    assert_eq!(2, stored.len());
+    assert_eq!(Some(282.55), stored[0].main.as_ref().unwrap().temp);
+    assert_eq!(Some("London".to_string()), stored[0].name);
+
+    match store.insert(&WeatherInfo::default()) {
+        Err(err @ Error::InvalidObservation(_)) => assert_eq!(ErrorKind::InvalidRequest, err.kind()),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn persistence() {
+    let path = std::env::temp_dir().join(format!("owm-store-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let find: WeatherAggregate = serde_json::from_str(include_str!("../fixtures/find.json")).unwrap();
+    let list = find.list.unwrap();
+    assert_eq!(list.len(), Store::open(&path).unwrap().insert_all(&list).unwrap());
+
+    let mut store = Store::open(&path).unwrap();
+    assert_eq!(0, store.insert_all(&list).unwrap());
+    assert_eq!(list.len(), store.latest_all().unwrap().len());
+
+    // A failing batch stores nothing
+    assert!(store.insert_all(vec![&clear(), &WeatherInfo::default()]).is_err());
+    assert!(store.latest(2643743).unwrap().is_none());
+
+    drop(store);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn queries() {
+    let mut store = Store::in_memory().unwrap();
+    let mut zocca: WeatherInfo =
+        serde_json::from_str(include_str!("../fixtures/weather_rain.json")).unwrap();
+    zocca.dt = Some(1560340000);
+    store.insert_all(vec![&london(1560330000, 280.0),
+                          &london(1560340000, 284.0),
+                          &london(1560350000, 282.0),
+                          &zocca])
+        .unwrap();
+
+    let range = store.range(2643743, 1560340000..1560350000).unwrap();
+    assert_eq!(vec![Some(1560340000)], range.iter().map(|info| info.dt).collect::<Vec<_>>());
+    assert!(store.range(1, 0..i64::MAX).unwrap().is_empty());
+
+    assert_eq!(Some(1560350000), store.latest(2643743).unwrap().unwrap().dt);
+    assert!(store.latest(1).unwrap().is_none());
+
+    let latest = store.latest_all().unwrap();
+    assert_eq!(vec![Some(2643743), Some(3163858)],
+               latest.iter().map(|info| info.id).collect::<Vec<_>>());
+    assert_eq!(Some(1560350000), latest[0].dt);
+}
+
+#[test]
+fn daily_rollups() {
+    let mut store = Store::in_memory().unwrap();
+    // 2019-06-12 22:30 UTC is still on the 12th in London, 23:30 UTC is on the 13th
+    store.insert_all(vec![&london(1560371400, 285.0),
+                          &london(1560378600, 283.0),
+                          &london(1560382200, 281.0),
+                          &london(1560400200, 290.0)])
+        .unwrap();
+
+    let mut no_temp = london(1560390000, 0.0);
+    no_temp.main = None;
+    store.insert(&no_temp).unwrap();
+
+    let days = store.daily(2643743, Field::Temperature, 0..i64::MAX).unwrap();
+    assert_eq!(vec![DailyRollup {
+                        start: 1560294000,
+                        count: 2,
+                        min: 283.0,
+                        max: 285.0,
+                        mean: 284.0,
+                    },
+                    DailyRollup {
+                        start: 1560380400,
+                        count: 2,
+                        min: 281.0,
+                        max: 290.0,
+                        mean: 285.5,
+                    }],
+               days);
+
+    assert!(store.daily(2643743, Field::Snow, 0..i64::MAX).unwrap().is_empty());
+}
@@ -0,0 +1,86 @@
+extern crate owm;
+extern crate serde_json;
+
+use self::owm::data::WeatherInfo;
+use self::owm::template::{severity, IconSet, Severity, Template};
+use self::owm::{Error, Units};
+
+fn fixture(body: &str) -> WeatherInfo {
+    serde_json::from_str(body).unwrap()
+}
+
+#[test]
+fn placeholders() {
+    let info = fixture(include_str!("../fixtures/weather_clear.json"));
+
+    let template = Template::parse("{city}, {country}: {temp:.0}°{units} {condition_icon} \
+                                    {wind_speed} {speed_units} {wind_compass}")
+        .unwrap();
+    assert_eq!("London, GB: 283°K ☀ 1.5 m/s N", template.render(&info));
+
+    let template = Template::parse("{temp:.2}°{units} {{{humidity}%}} {rain} {description}")
+        .unwrap()
+        .units(Units::Metric);
+    assert_eq!("282.55°C {100%} - clear sky", template.render(&info));
+
+    // Kelvins take no degree sign
+    let template = Template::parse("{temp:.0}{temp_units}").unwrap();
+    assert_eq!("283 K", template.render(&info));
+    assert_eq!("283°F", template.units(Units::Imperial).render(&info));
+
+    assert_eq!("", Template::parse("").unwrap().render(&info));
+    assert_eq!("-", Template::parse("{temp}").unwrap().render(&WeatherInfo::default()));
+}
+
+#[test]
+fn invalid_templates() {
+    for text in &["{temperature}", "{temp", "temp}", "{temp:1}", "{temp:.x}", "{te{mp}"] {
+        match Template::parse(text) {
+            Err(Error::InvalidTemplate(msg)) => assert!(msg.contains(text), "{}", msg),
+            other => panic!("{}: {:?}", text, other),
+        }
+    }
+}
+
+#[test]
+fn icon_sets() {
+    let info = fixture(include_str!("../fixtures/weather_snow.json"));
+    let icon = |icons| Template::parse("{condition_icon}").unwrap().icons(icons).render(&info);
+
+    assert_eq!("🌨", icon(IconSet::Emoji));
+    assert_eq!("\u{e31a}", icon(IconSet::NerdFont));
+    assert_eq!("\u{f01b}", icon(IconSet::WeatherIcons));
+
+    assert_eq!("☀", IconSet::Emoji.icon(800, false));
+    assert_eq!("🌙", IconSet::Emoji.icon(800, true));
+    assert_eq!("🌪", IconSet::Emoji.icon(781, false));
+}
+
+#[test]
+fn severities() {
+    assert_eq!(Severity::Normal, Severity::of(800));
+    assert_eq!(Severity::Normal, Severity::of(701));
+    assert_eq!(Severity::Warning, Severity::of(501));
+    assert_eq!(Severity::Warning, Severity::of(741));
+    assert_eq!(Severity::Critical, Severity::of(511));
+    assert_eq!(Severity::Critical, Severity::of(781));
+    assert_eq!(None, severity(&WeatherInfo::default()));
+}
+
+#[test]
+fn status_bars() {
+    let template = Template::parse("{temp:.0}°{units}").unwrap().units(Units::Metric);
+    let clear = fixture(include_str!("../fixtures/weather_clear.json"));
+    let rain = fixture(include_str!("../fixtures/weather_rain.json"));
+
+    assert_eq!(json!({"name": "weather", "full_text": "283°C"}), template.i3bar(&clear));
+    let block = template.i3bar(&rain);
+    assert_eq!("#FFCC00", block["color"]);
+    assert!(block.get("urgent").is_none());
+
+    let module = template.waybar(&rain);
+    assert_eq!("298°C", module["text"]);
+    assert_eq!("warning", module["class"]);
+    assert_eq!("rain", module["alt"]);
+    assert!(module["tooltip"].as_str().unwrap().starts_with("Zocca: moderate rain, feels like 299°C\n"));
+}
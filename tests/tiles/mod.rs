@@ -0,0 +1,123 @@
+extern crate owm;
+extern crate serde_json;
+
+use std::time::{Duration, Instant};
+use self::owm::{BoundingBox, Error, LatLon};
+use self::owm::current::MAX_BOX_AREA;
+use mock::{MockRequest, MockServer};
+
+/// (id, name, lat, lon) of the cities known to the mock server.
+const CITIES: [(i64, &str, f64, f64); 5] = [(3173435, "Livorno", 43.55, 10.3167),
+                                            (6542122, "Pisa", 43.7085, 10.4036),
+                                            (3176959, "Firenze", 43.77925, 11.24626),
+                                            (3169070, "Rome", 41.894741, 12.4839),
+                                            (2523920, "Palermo", 38.1166, 13.3667)];
+
+/// Answers box/city queries with the cities within the box, edges included,
+/// so that cities on the border between two tiles are reported by both.
+fn box_city(req: &MockRequest) -> (u16, String) {
+    let bbox: Vec<f64> = req.param("bbox")
+        .unwrap()
+        .split(',')
+        .map(|v| v.parse().unwrap())
+        .collect();
+    let bbox = BoundingBox::new(bbox[3], bbox[1], bbox[0], bbox[2]).unwrap();
+
+    let list: Vec<serde_json::Value> = CITIES.iter()
+        .filter(|&&(_, _, lat, lon)| bbox.contains(&LatLon::new(lat, lon).unwrap()))
+        .map(|&(id, name, lat, lon)| {
+            json!({"id": id, "name": name, "coord": {"Lat": lat, "Lon": lon}, "main": {"temp": 20.0}})
+        })
+        .collect();
+
+    (200, json!({"cod": 200, "calctime": 0.01, "cnt": list.len(), "list": list}).to_string())
+}
+
+fn ids(list: &[owm::data::WeatherInfo]) -> Vec<i64> {
+    let mut ids: Vec<i64> = list.iter().map(|info| info.id.unwrap()).collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn tile_grid() {
+    let bbox = BoundingBox::new(46.0, 36.0, 6.0, 19.0).unwrap();
+    let tiles = bbox.tiles(5.0).unwrap();
+
+    assert_eq!(6, tiles.len());
+    assert!(tiles.iter().all(|t| t.width() <= 5.0 && t.height() <= 5.0));
+    assert!(tiles.iter().all(|t| t.width() * t.height() <= MAX_BOX_AREA));
+
+    let total: f64 = tiles.iter().map(|t| t.area()).sum();
+    assert!((total - bbox.area()).abs() < 1e-6 * bbox.area());
+
+    assert_eq!(vec![bbox], bbox.tiles(20.0).unwrap());
+
+    let crossing = BoundingBox::new(-10.0, -25.0, 175.0, -170.0).unwrap();
+    let tiles = crossing.tiles(5.0).unwrap();
+    assert_eq!(3 * 3, tiles.len());
+    assert!(tiles.iter().all(|t| !t.crosses_antimeridian()));
+}
+
+#[test]
+fn invalid_tile_sizes() {
+    let bbox = BoundingBox::new(46.0, 36.0, 6.0, 19.0).unwrap();
+    for &size in &[0.0, -5.0, f64::NAN, 1e-9] {
+        match bbox.tiles(size) {
+            Err(Error::InvalidCoordinates(_)) => (),
+            res => panic!("unexpected result for {}: {:?}", size, res.map(|tiles| tiles.len())),
+        }
+    }
+    assert_eq!(1, bbox.tiles(f64::INFINITY).unwrap().len());
+}
+
+#[test]
+fn tiled_query_merges_tiles() {
+    let server = MockServer::new(box_city);
+    let hub = server.hub();
+
+    // Pisa lies on the border between two tiles
+    let bbox = BoundingBox::new(48.7085, 38.7085, 5.4036, 15.4036).unwrap();
+    let (_, agg) = hub.current().by_bounds_tiled(&bbox, 10, false).unwrap();
+
+    let requests = server.requests();
+    assert_eq!(4, requests.len());
+    assert!(requests.iter().all(|r| r.path == "/data/2.5/box/city"));
+    assert!(requests.iter().all(|r| r.param("appid") == Some("test-key")));
+    assert!(requests.iter().all(|r| r.param("cluster") == Some("no")));
+
+    let list = agg.list.unwrap();
+    assert_eq!(vec![3169070, 3173435, 3176959, 6542122], ids(&list));
+    assert_eq!(Some(4), agg.cnt);
+}
+
+#[test]
+fn bounds_across_antimeridian() {
+    let server = MockServer::new(|_: &MockRequest| {
+        (200, r#"{"cod":200,"calctime":0.01,"cnt":0,"list":[]}"#.to_string())
+    });
+    let hub = server.hub();
+
+    let bbox = BoundingBox::new(-10.0, -25.0, 175.0, -170.0).unwrap();
+    let (_, agg) = hub.current().by_bounds(&bbox, 10, true).unwrap();
+
+    let bboxes: Vec<String> = server.requests()
+        .iter()
+        .map(|r| r.param("bbox").unwrap().to_string())
+        .collect();
+    assert_eq!(vec!["175,-25,180,-10,10", "-180,-25,-170,-10,10"], bboxes);
+    assert_eq!(Some(0), agg.cnt);
+}
+
+#[test]
+fn tiled_query_respects_rate_limit() {
+    let server = MockServer::new(box_city);
+    let hub = server.hub().rate_limit(600);
+
+    let bbox = BoundingBox::new(46.0, 36.0, 6.0, 16.0).unwrap();
+    let start = Instant::now();
+    hub.current().by_bounds_tiled(&bbox, 10, false).unwrap();
+
+    assert_eq!(4, server.requests().len());
+    assert!(start.elapsed() >= Duration::from_millis(300));
+}
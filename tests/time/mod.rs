@@ -0,0 +1,43 @@
+extern crate chrono;
+extern crate owm;
+extern crate serde_json;
+
+use self::chrono::{TimeZone, Timelike, Utc};
+use self::owm::data::WeatherInfo;
+
+fn fixture() -> WeatherInfo {
+    serde_json::from_str(include_str!("../fixtures/weather_rain.json")).unwrap()
+}
+
+#[test]
+fn utc_timestamps() {
+    let info = fixture();
+
+    assert_eq!(Some(Utc.with_ymd_and_hms(2022, 8, 30, 14, 43, 12).unwrap()),
+               info.dt_utc());
+    assert_eq!(Some(Utc.with_ymd_and_hms(2022, 8, 30, 4, 36, 27).unwrap()),
+               info.sys.clone().unwrap().sunrise_utc());
+}
+
+#[test]
+fn local_timestamps() {
+    let info = fixture();
+
+    let local = info.dt_local().unwrap();
+    assert_eq!(7200, local.offset().local_minus_utc());
+    assert_eq!(16, local.hour());
+    assert_eq!(info.dt_utc(), Some(local.with_timezone(&Utc)));
+
+    assert_eq!(6, info.sunrise_local().unwrap().hour());
+    assert_eq!(19, info.sunset_local().unwrap().hour());
+}
+
+#[test]
+fn missing_timezone() {
+    let mut info = fixture();
+    info.timezone = None;
+
+    assert!(info.dt_utc().is_some());
+    assert_eq!(None, info.dt_local());
+    assert_eq!(None, info.sunrise_local());
+}
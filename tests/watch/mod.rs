@@ -0,0 +1,156 @@
+extern crate owm;
+
+use std::sync::mpsc;
+use std::time::Duration;
+use self::owm::data::Field;
+use self::owm::watch::{Change, Clock, ManualClock, Precipitation, Watcher, DEFAULT_INTERVAL};
+use self::owm::{ErrorKind, Location};
+use mock::{MockRequest, MockServer};
+
+/// Serves clear sky, then rain, then a server error, then snow, one per request.
+fn server() -> MockServer {
+    let server_requests = std::sync::atomic::AtomicUsize::new(0);
+
+    MockServer::new(move |_: &MockRequest| {
+        match server_requests.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+            0 => (200, include_str!("../fixtures/weather_clear.json").to_string()),
+            1 => (200, include_str!("../fixtures/weather_rain.json").to_string()),
+            2 => (500, "Internal Server Error".to_string()),
+            _ => (200, include_str!("../fixtures/weather_snow.json").to_string()),
+        }
+    })
+}
+
+#[test]
+fn poll_reports_changes() {
+    let server = server();
+    let hub = server.hub();
+    let clock = ManualClock::new();
+    let london = Location::id(2643743);
+    let mut watcher = Watcher::with_clock(&hub, &clock)
+        .threshold(Field::Temperature, 290.0)
+        .threshold(Field::Humidity, 50.0)
+        .track(london.clone());
+
+    assert!(watcher.poll().is_empty());
+    assert_eq!(Some(800), watcher.last(&london).unwrap().weather.as_ref().unwrap()[0].id);
+
+    // Not due yet
+    clock.advance(DEFAULT_INTERVAL - Duration::from_secs(1));
+    assert!(watcher.poll().is_empty());
+    assert_eq!(1, server.requests().len());
+
+    clock.advance(Duration::from_secs(1));
+    let events = watcher.poll();
+    assert_eq!(3, events.len());
+    assert!(events.iter().all(|event| event.location == london));
+
+    match events[0].change {
+        Change::ConditionChanged { ref from, ref to } => {
+            assert_eq!((Some(800), Some(501)), (from.id, to.id));
+        }
+        ref other => panic!("{:?}", other),
+    }
+    match events[1].change {
+        Change::ThresholdCrossed { field, threshold, from, to } => {
+            assert_eq!((Field::Temperature, 290.0), (field, threshold));
+            assert!(from < 290.0 && to > 290.0);
+        }
+        ref other => panic!("{:?}", other),
+    }
+    match events[2].change {
+        Change::PrecipitationStarted(Precipitation::Rain) => (),
+        ref other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn threshold_reached_exactly() {
+    // Temperatures served one per request
+    let temps = [285.0, 290.0, 295.0, 290.0, 285.0];
+    let requests = std::sync::atomic::AtomicUsize::new(0);
+    let server = MockServer::new(move |_: &MockRequest| {
+        let temp = temps[requests.fetch_add(1, std::sync::atomic::Ordering::SeqCst)];
+        let body = include_str!("../fixtures/weather_clear.json")
+            .replace("\"temp\":282.55", &format!("\"temp\":{:.1}", temp));
+        (200, body)
+    });
+    let hub = server.hub();
+    let clock = ManualClock::new();
+    let mut watcher = Watcher::with_clock(&hub, &clock)
+        .threshold(Field::Temperature, 290.0)
+        .track(Location::id(2643743));
+
+    let mut crossings = Vec::new();
+    for _ in 0..temps.len() {
+        for event in watcher.poll() {
+            match event.change {
+                Change::ThresholdCrossed { from, to, .. } => crossings.push((from, to)),
+                other => panic!("{:?}", other),
+            }
+        }
+        clock.advance(DEFAULT_INTERVAL);
+    }
+
+    // The threshold itself counts as above it
+    assert_eq!(vec![(285.0, 290.0), (290.0, 285.0)], crossings);
+}
+
+#[test]
+fn run_delivers_events() {
+    let server = server();
+    let hub = server.hub();
+    let clock = ManualClock::new();
+    let start = clock.now();
+    let mut watcher = Watcher::with_clock(&hub, &clock)
+        .interval(Duration::from_secs(60))
+        .track(Location::name("Moscow", Some("RU")));
+
+    let mut changes = Vec::new();
+    watcher.run(|event| {
+        changes.push(event.change);
+        changes.len() < 6
+    });
+
+    // Clear, rain, failure, snow: the last observation before the failure is kept
+    assert_eq!(4, server.requests().len());
+    assert_eq!(Duration::from_secs(180), clock.now() - start);
+    match changes[2] {
+        Change::QueryFailed(ErrorKind::Server) => (),
+        ref other => panic!("{:?}", other),
+    }
+    match changes[3] {
+        Change::ConditionChanged { ref from, ref to } => {
+            assert_eq!((Some(501), Some(600)), (from.id, to.id));
+        }
+        ref other => panic!("{:?}", other),
+    }
+    match (&changes[4], &changes[5], watcher.last(&Location::name("Moscow", Some("RU")))) {
+        (&Change::PrecipitationStopped(Precipitation::Rain),
+         &Change::PrecipitationStarted(Precipitation::Snow),
+         Some(info)) => {
+            assert_eq!(Some("Moscow".to_string()), info.name);
+        }
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn run_with_channel() {
+    let server = server();
+    let hub = server.hub();
+    let clock = ManualClock::new();
+    let mut watcher = Watcher::with_clock(&hub, &clock).track(Location::id(1));
+
+    let (sender, receiver) = mpsc::channel();
+    let events = std::thread::scope(|scope| {
+        scope.spawn(move || watcher.run_with(sender));
+
+        let events: Vec<_> = receiver.iter().take(2).collect();
+        drop(receiver);
+        events
+    });
+
+    assert_eq!(2, events.len());
+    assert_eq!(Location::id(1), events[0].location);
+}
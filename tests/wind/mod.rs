@@ -0,0 +1,79 @@
+extern crate owm;
+
+use self::owm::Units;
+use self::owm::data::{CompassPoints, Wind};
+
+fn wind(speed: f32, deg: f32) -> Wind {
+    Wind {
+        speed: Some(speed),
+        deg: Some(deg),
+        ..Default::default()
+    }
+}
+
+fn assert_close(expected: f32, actual: f32) {
+    assert!((expected - actual).abs() < 1e-3,
+            "expected {}, got {}",
+            expected,
+            actual);
+}
+
+#[test]
+fn compass_points() {
+    assert_eq!(Some("N"), wind(1.0, 0.0).compass(CompassPoints::Eight));
+    assert_eq!(Some("N"), wind(1.0, 359.0).compass(CompassPoints::Sixteen));
+    assert_eq!(Some("NE"), wind(1.0, 30.0).compass(CompassPoints::Eight));
+    assert_eq!(Some("NNE"), wind(1.0, 30.0).compass(CompassPoints::Sixteen));
+    assert_eq!(Some("NEbN"), wind(1.0, 33.75).compass(CompassPoints::ThirtyTwo));
+    assert_eq!(Some("WSW"), wind(1.0, 247.5).compass(CompassPoints::Sixteen));
+    assert_eq!(Some("NbW"), wind(1.0, 350.0).compass(CompassPoints::ThirtyTwo));
+    assert_eq!(Some("S"), wind(1.0, -180.0).compass(CompassPoints::Eight));
+    assert_eq!(None, Wind::default().compass(CompassPoints::Eight));
+}
+
+#[test]
+fn beaufort_scale() {
+    assert_eq!(Some(0), wind(0.2, 0.0).beaufort(Units::Metric));
+    assert_eq!(Some(3), wind(5.0, 0.0).beaufort(Units::Metric));
+    assert_eq!(Some(4), wind(5.5, 0.0).beaufort(Units::Standard));
+    assert_eq!(Some(12), wind(40.0, 0.0).beaufort(Units::Metric));
+    assert_eq!(Some(6), wind(25.0, 0.0).beaufort(Units::Imperial));
+    assert_eq!(Some("Gentle breeze"),
+               wind(5.0, 0.0).beaufort_description(Units::Metric));
+    assert_eq!(Some("Hurricane force"),
+               wind(40.0, 0.0).beaufort_description(Units::Metric));
+    assert_eq!(None, Wind::default().beaufort(Units::Metric));
+}
+
+#[test]
+fn vector_components() {
+    let (u, v) = wind(10.0, 270.0).components().unwrap();
+    assert_close(10.0, u);
+    assert_close(0.0, v);
+
+    let (u, v) = wind(10.0, 0.0).components().unwrap();
+    assert_close(0.0, u);
+    assert_close(-10.0, v);
+
+    let back = Wind::from_components(3.0, -4.0);
+    assert_close(5.0, back.speed.unwrap());
+    let (u, v) = back.components().unwrap();
+    assert_close(3.0, u);
+    assert_close(-4.0, v);
+}
+
+#[test]
+fn vector_average() {
+    let mut gusty = wind(10.0, 10.0);
+    gusty.gust = Some(15.0);
+
+    let avg = Wind::average(&[wind(10.0, 350.0), gusty, Wind::default()]).unwrap();
+    assert_close(0.0, avg.deg.unwrap());
+    assert_close(10.0 * 10f32.to_radians().cos(), avg.speed.unwrap());
+    assert_eq!(Some(15.0), avg.gust);
+
+    let calm = Wind::average(&[wind(5.0, 90.0), wind(5.0, 270.0)]).unwrap();
+    assert_close(0.0, calm.speed.unwrap());
+
+    assert!(Wind::average(&[Wind::default()]).is_none());
+}